@@ -28,10 +28,15 @@
 /// which attributes should go on the type and which should go on the impl
 /// block. The latter should go on the right-hand side of the arrow operator.
 ///
+/// With the `serde` feature of this crate, `Serialize` and `Deserialize` are
+/// also implemented. Known variants are serialized as their name and unknown
+/// ones as their integer value. Both forms are accepted when deserializing,
+/// which requires a self-describing format.
+///
 /// Usage example:
 /// ```
 /// newtype_enum! {
-/// #[derive(Cmp, PartialCmp)]
+/// #[derive(Hash, Ord, PartialOrd)]
 /// pub enum UnixBool: i32 => #[allow(missing_docs)] {
 ///     FALSE          =  0,
 ///     TRUE           =  1,
@@ -79,5 +84,128 @@ macro_rules! newtype_enum {
                 }
             }
         }
+
+        $crate::__newtype_enum_serde!($type: $base_integer => $($variant),*);
     }
 }
+
+/// Implementation detail of `newtype_enum!`, implementing the serde traits
+#[doc(hidden)]
+#[cfg(feature = "serde")]
+#[macro_export]
+macro_rules! __newtype_enum_serde {
+    ($type:ident : $base_integer:ty => $($variant:ident),*) => {
+        const _: () = {
+            use $crate::__serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+            impl Serialize for $type {
+                fn serialize<S: Serializer>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error> {
+                    match *self {
+                        $(
+                            $type::$variant => serializer.serialize_str(stringify!($variant)),
+                        )*
+                        $type(unknown) => $crate::__NewtypeEnumBase::serialize_raw(&unknown, serializer),
+                    }
+                }
+            }
+
+            struct Visitor;
+
+            impl<'de> de::Visitor<'de> for Visitor {
+                type Value = $type;
+
+                fn expecting(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                    write!(f, "a {} variant name or integer", stringify!($type))
+                }
+
+                fn visit_str<E: de::Error>(self, name: &str) -> ::core::result::Result<$type, E> {
+                    $(
+                        if name == stringify!($variant) {
+                            return Ok($type::$variant);
+                        }
+                    )*
+                    Err(E::unknown_variant(name, &[$(stringify!($variant)),*]))
+                }
+
+                fn visit_u64<E: de::Error>(self, value: u64) -> ::core::result::Result<$type, E> {
+                    <$base_integer as $crate::__NewtypeEnumBase>::from_u64(value)
+                        .map($type)
+                        .ok_or_else(|| E::invalid_value(de::Unexpected::Unsigned(value), &self))
+                }
+
+                fn visit_i64<E: de::Error>(self, value: i64) -> ::core::result::Result<$type, E> {
+                    <$base_integer as $crate::__NewtypeEnumBase>::from_i64(value)
+                        .map($type)
+                        .ok_or_else(|| E::invalid_value(de::Unexpected::Signed(value), &self))
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $type {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> ::core::result::Result<$type, D::Error> {
+                    deserializer.deserialize_any(Visitor)
+                }
+            }
+        };
+    };
+}
+
+/// Implementation detail of `newtype_enum!`, for the serde implementations
+/// of unknown variants
+///
+/// This is implemented for the integer types and for `Guid`. Unknown GUIDs
+/// are serialized in their textual form, but cannot be deserialized.
+#[doc(hidden)]
+#[cfg(feature = "serde")]
+pub trait NewtypeEnumBase: Sized {
+    fn serialize_raw<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error>;
+    fn from_u64(value: u64) -> Option<Self>;
+    fn from_i64(value: i64) -> Option<Self>;
+}
+
+#[cfg(feature = "serde")]
+macro_rules! impl_newtype_enum_base {
+    ($($ty:ty),*) => {
+        $(
+            impl NewtypeEnumBase for $ty {
+                fn serialize_raw<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    serde::Serialize::serialize(self, serializer)
+                }
+
+                fn from_u64(value: u64) -> Option<Self> {
+                    core::convert::TryFrom::try_from(value).ok()
+                }
+
+                fn from_i64(value: i64) -> Option<Self> {
+                    core::convert::TryFrom::try_from(value).ok()
+                }
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "serde")]
+impl_newtype_enum_base!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+#[cfg(feature = "serde")]
+impl NewtypeEnumBase for super::Guid {
+    fn serialize_raw<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+
+    fn from_u64(_: u64) -> Option<Self> {
+        None
+    }
+
+    fn from_i64(_: i64) -> Option<Self> {
+        None
+    }
+}
+
+/// Implementation detail of `newtype_enum!`, which does nothing without the
+/// `serde` feature
+#[doc(hidden)]
+#[cfg(not(feature = "serde"))]
+#[macro_export]
+macro_rules! __newtype_enum_serde {
+    ($($tokens:tt)*) => {};
+}
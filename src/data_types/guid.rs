@@ -57,6 +57,78 @@ impl Guid {
     }
 }
 
+impl Guid {
+    /// Positions of the dashes in the canonical textual format
+    const DASHES: [usize; 4] = [8, 13, 18, 23];
+
+    /// Format this GUID in the canonical textual format, without allocating
+    ///
+    /// This is the lowercase form printed by the `Display` formatter.
+    pub fn to_ascii_bytes(&self) -> [u8; 36] {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+        let mut out = [b'-'; 36];
+        let digits = (0..36).filter(|i| !Guid::DASHES.contains(i));
+        for (position, nibble) in digits.zip(self.nibbles()) {
+            out[position] = HEX[usize::from(nibble)];
+        }
+        out
+    }
+
+    /// Parse a GUID in the canonical textual format, without allocating
+    ///
+    /// Both lowercase and uppercase hexadecimal digits are accepted.
+    pub fn from_ascii(text: &[u8]) -> Result<Guid, ParseGuidError> {
+        if text.len() != 36 {
+            return Err(ParseGuidError);
+        }
+        let mut bytes = [0u8; 16];
+        let mut digits = 0;
+        for (position, &c) in text.iter().enumerate() {
+            if Guid::DASHES.contains(&position) {
+                if c != b'-' {
+                    return Err(ParseGuidError);
+                }
+                continue;
+            }
+            let nibble = (c as char).to_digit(16).ok_or(ParseGuidError)? as u8;
+            bytes[digits / 2] |= nibble << (4 * (1 - digits % 2));
+            digits += 1;
+        }
+        let mut d = [0u8; 8];
+        d.copy_from_slice(&bytes[8..]);
+        Ok(Guid {
+            a: u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            b: u16::from_be_bytes([bytes[4], bytes[5]]),
+            c: u16::from_be_bytes([bytes[6], bytes[7]]),
+            d,
+        })
+    }
+
+    /// Hexadecimal digits of this GUID, in the order they are printed
+    fn nibbles(&self) -> impl Iterator<Item = u8> {
+        let a = self.a.to_be_bytes();
+        let b = self.b.to_be_bytes();
+        let c = self.c.to_be_bytes();
+        let mut bytes = [0u8; 16];
+        bytes[..4].copy_from_slice(&a);
+        bytes[4..6].copy_from_slice(&b);
+        bytes[6..8].copy_from_slice(&c);
+        bytes[8..].copy_from_slice(&self.d);
+        (0..32).map(move |i| (bytes[i / 2] >> (4 * (1 - i % 2))) & 0xf)
+    }
+}
+
+/// Error returned when parsing a GUID which is not in the canonical textual
+/// format
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ParseGuidError;
+
+impl fmt::Display for ParseGuidError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid GUID string")
+    }
+}
+
 impl fmt::Display for Guid {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         let d = {
@@ -0,0 +1,57 @@
+//! Hexadecimal dump of byte buffers
+//!
+//! This is meant for debugging drivers over a serial console, for instance
+//! to print configuration space or a memory-mapped window read from a
+//! device, without needing an allocator.
+
+use core::fmt;
+
+/// Number of bytes displayed on each line
+const BYTES_PER_LINE: usize = 16;
+
+/// Displays a byte buffer in the classic hexdump layout
+///
+/// Each line holds the offset of its first byte, 16 bytes in hexadecimal
+/// and the same bytes as ASCII characters, unprintable ones being replaced
+/// by dots:
+///
+/// ```text
+/// 00000000  86 80 0e 10 07 00 00 00  02 00 00 02 00 00 00 00  |................|
+/// ```
+///
+/// Lines are separated by newlines, with no newline after the last one.
+#[derive(Clone, Copy)]
+pub struct HexDump<'a>(pub &'a [u8]);
+
+impl fmt::Display for HexDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, line) in self.0.chunks(BYTES_PER_LINE).enumerate() {
+            if index != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{:08x} ", index * BYTES_PER_LINE)?;
+            for position in 0..BYTES_PER_LINE {
+                if position % 8 == 0 {
+                    write!(f, " ")?;
+                }
+                match line.get(position) {
+                    Some(byte) => write!(f, "{:02x} ", byte)?,
+                    None => write!(f, "   ")?,
+                }
+            }
+            write!(f, " |")?;
+            for &byte in line {
+                let c = if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' };
+                write!(f, "{}", c)?;
+            }
+            write!(f, "|")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for HexDump<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
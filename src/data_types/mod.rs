@@ -5,7 +5,7 @@
 use core::{ffi::c_void, mem::MaybeUninit};
 
 /// Opaque handle to an UEFI entity (protocol, image...)
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 #[repr(transparent)]
 pub struct Handle(*mut c_void);
 
@@ -17,6 +17,10 @@ impl Handle {
     pub(crate) fn null() -> Self {
         Handle(core::ptr::null_mut())
     }
+
+    pub(crate) fn is_null(&self) -> bool {
+        self.0.is_null()
+    }
 }
 
 /// Handle to an event structure
@@ -44,7 +48,7 @@ pub trait Align {
 }
 
 mod guid;
-pub use self::guid::Guid;
+pub use self::guid::{Guid, ParseGuidError};
 pub use self::guid::{unsafe_guid, Identify};
 
 pub mod chars;
@@ -52,6 +56,12 @@ pub use self::chars::{Char16, Char8};
 
 #[macro_use]
 mod enums;
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub use self::enums::NewtypeEnumBase;
 
 mod strs;
 pub use self::strs::{CStr16, CStr8};
+
+mod hexdump;
+pub use self::hexdump::HexDump;
@@ -154,6 +154,15 @@ impl CStr16 {
         unsafe { &*(&self.0 as *const [Char16] as *const [u16]) }
     }
 
+    /// Decode this C string into characters
+    ///
+    /// The code units are decoded as UTF-16, of which UCS-2 is a subset.
+    /// Unpaired surrogates are replaced with U+FFFD REPLACEMENT CHARACTER.
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        core::char::decode_utf16(self.to_u16_slice().iter().copied())
+            .map(|c| c.unwrap_or(core::char::REPLACEMENT_CHARACTER))
+    }
+
     /// Returns an iterator over this C string
     pub fn iter(&self) -> CStr16Iter {
         CStr16Iter {
@@ -189,6 +198,21 @@ impl fmt::Debug for CStr16 {
     }
 }
 
+impl PartialEq<str> for CStr16 {
+    /// Compares code unit by code unit, without allocating. Strings with
+    /// characters outside of the Basic Multilingual Plane never compare
+    /// equal, since UCS-2 cannot represent them.
+    fn eq(&self, other: &str) -> bool {
+        self.to_u16_slice().iter().copied().eq(other.encode_utf16())
+    }
+}
+
+impl PartialEq<&str> for CStr16 {
+    fn eq(&self, other: &&str) -> bool {
+        *self == **other
+    }
+}
+
 impl fmt::Display for CStr16 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         for c in self.iter() {
@@ -40,6 +40,14 @@
 #[cfg(feature = "exts")]
 extern crate alloc as alloc_api;
 
+// Used by the serde implementations generated by `newtype_enum!`
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub use serde as __serde;
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub use self::data_types::NewtypeEnumBase as __NewtypeEnumBase;
+
 #[macro_use]
 pub mod data_types;
 pub use self::data_types::{unsafe_guid, Identify};
@@ -47,6 +55,8 @@ pub use self::data_types::{CStr16, CStr8, Char16, Char8, Event, Guid, Handle};
 
 mod result;
 pub use self::result::{Completion, Result, ResultExt, Status};
+#[cfg(feature = "exts")]
+pub use self::result::{partition_warnings, WarningAccumulator};
 
 pub mod table;
 
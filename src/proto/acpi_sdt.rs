@@ -0,0 +1,78 @@
+//! `AcpiSdt` protocol, from the PI specification.
+
+use crate::proto::Protocol;
+use crate::proto::acpi_table::{SdtHeader, TableKey};
+use crate::{unsafe_guid, Completion, Result, Status};
+use core::ptr;
+
+type GetAcpiTableFn = extern "efiapi" fn(
+    index: usize,
+    table: *mut *const SdtHeader,
+    version: *mut u32,
+    table_key: *mut usize) -> Status;
+
+/// The ACPI System Description Table protocol of the PI specification, used
+/// here to enumerate the installed ACPI tables.
+///
+/// Only table enumeration is exposed. The keys it returns are the ones the
+/// `AcpiTable` protocol uninstalls tables with, including the tables which
+/// were installed by the platform.
+#[repr(C)]
+#[unsafe_guid("eb97088e-cfdf-49c6-be4b-d906a5b20e86")]
+#[derive(Protocol)]
+pub struct AcpiSdt {
+    acpi_version: u32,
+    get_acpi_table: GetAcpiTableFn,
+    register_notify: usize,
+    open: usize,
+    open_sdt: usize,
+    close: usize,
+    get_child: usize,
+    get_option: usize,
+    set_option: usize,
+    find_path: usize
+}
+
+impl AcpiSdt {
+    /// Retrieve the installed table at the given index, along with the key
+    /// needed to uninstall it.
+    ///
+    /// Fails with `NOT_FOUND` once the index is past the last table. The
+    /// table belongs to the firmware and is only valid until it gets
+    /// uninstalled.
+    pub fn table(&self, index: usize) -> Result<(&[u8], TableKey)> {
+        let mut table = ptr::null();
+        let mut version = 0;
+        let mut table_key = 0;
+        let status = (self.get_acpi_table)(index, &mut table, &mut version, &mut table_key);
+        if !status.is_error() && table.is_null() {
+            return Err(Status::NOT_FOUND.into());
+        }
+        status.into_with_val(|| {
+            let length = unsafe { (*table).length } as usize;
+            let bytes = unsafe { core::slice::from_raw_parts(table.cast::<u8>(), length) };
+            (bytes, TableKey::new(table_key))
+        })
+    }
+
+    /// Find the first installed table with the given signature, such as
+    /// `*b"SSDT"`.
+    ///
+    /// Warnings encountered while enumerating the tables are logged.
+    pub fn find(&self, signature: [u8; 4]) -> Result<Option<(&[u8], TableKey)>> {
+        for index in 0.. {
+            match self.table(index) {
+                Ok(completion) => {
+                    let (status, (bytes, key)) = completion.split();
+                    if bytes.get(..4) == Some(&signature[..]) {
+                        return status.into_with_val(|| Some((bytes, key)));
+                    }
+                    Completion::new(status, ()).log();
+                }
+                Err(error) if error.status() == Status::NOT_FOUND => break,
+                Err(error) => return Err(error)
+            }
+        }
+        Ok(None.into())
+    }
+}
@@ -1,30 +1,356 @@
 use crate::proto::Protocol;
 use crate::{unsafe_guid, Status, Result};
+use core::convert::TryFrom;
 use core::ffi::c_void;
 
+#[cfg(feature = "exts")]
+use crate::proto::acpi_sdt::AcpiSdt;
+#[cfg(feature = "exts")]
+use crate::{Completion, ResultExt};
+#[cfg(feature = "exts")]
+use log::warn;
+#[cfg(feature = "exts")]
+use alloc_api::vec::Vec;
+
+type InstallTableFn = unsafe extern "efiapi" fn(
+    this: &AcpiTable,
+    buffer: *const c_void,
+    buffer_size: usize,
+    table_key: *mut usize) -> Status;
+
+type UninstallTableFn = unsafe extern "efiapi" fn(
+    this: &AcpiTable,
+    table_key: usize) -> Status;
+
+/// The ACPI Table protocol, used to install and uninstall ACPI tables.
+///
+/// Its GUID is `EFI_ACPI_TABLE_PROTOCOL_GUID` from the UEFI specification,
+/// which the test runner checks byte for byte.
 #[repr(C)]
 #[unsafe_guid("ffe06bdd-6107-46a6-7bb2-5a9c7ec5275c")]
 #[derive(Protocol)]
 pub struct AcpiTable {
-    install_table: unsafe extern "efiapi" fn(
-        this: &AcpiTable,
-        buffer: *const c_void,
-        buffer_size: usize,
-        table_key: *mut usize) -> Status,
-    uninstall_table: unsafe extern "efiapi" fn(
-        this: &AcpiTable,
-        table_key: usize) -> Status
+    install_table: InstallTableFn,
+    uninstall_table: UninstallTableFn
+}
+
+#[cfg(feature = "test-utils")]
+impl AcpiTable {
+    /// Build a protocol instance backed by the provided functions, for
+    /// testing code which uses `AcpiTable` without real firmware.
+    pub fn mock(install_table: InstallTableFn, uninstall_table: UninstallTableFn) -> AcpiTable {
+        AcpiTable {
+            install_table,
+            uninstall_table
+        }
+    }
 }
 
 impl AcpiTable {
-    pub unsafe fn install_acpi_table(&self, buffer: *const c_void, buffer_size: usize) -> Result<usize> {
+    /// Install an ACPI table, returning the key needed to uninstall it.
+    ///
+    /// The table is copied by the firmware, so the buffer can be released
+    /// once this returns. A warning reported by the firmware, for example
+    /// when a table with the same signature was already present, is kept
+    /// in the returned `Completion` along with the key; check its status
+    /// before discarding it with `log_warning` or `unwrap_success`.
+    ///
+    /// # Safety
+    ///
+    /// The buffer must point to `buffer_size` readable bytes holding a
+    /// complete ACPI table.
+    pub unsafe fn install_acpi_table(&self, buffer: *const c_void, buffer_size: usize) -> Result<TableKey> {
         let mut out_table_key = 0;
-        (self.install_table)(self, buffer, buffer_size, &mut out_table_key)
-            .into_with_val(|| out_table_key)
+        crate::efi_call!((self.install_table)(self, buffer, buffer_size, &mut out_table_key) => TableKey(out_table_key))
+    }
+
+    /// Uninstall a table previously installed by `install_acpi_table`, or
+    /// enumerated through the `AcpiSdt` protocol.
+    ///
+    /// The key is consumed, so a table cannot be uninstalled twice through
+    /// the same key. On error, it is handed back to the caller.
+    ///
+    /// # Safety
+    ///
+    /// The key must have been returned by `install_acpi_table` on this
+    /// protocol instance, or by `AcpiSdt` for a table which is still
+    /// installed. The firmware frees the table once it is uninstalled, so
+    /// no slice returned by `AcpiSdt` for it may be used afterwards.
+    pub unsafe fn uninstall_acpi_table(&self, table_key: TableKey) -> Result<(), TableKey> {
+        (self.uninstall_table)(self, table_key.0)
+            .into_with_err(|_| table_key)
+    }
+}
+
+impl AcpiTable {
+    /// Install an ACPI table held in a byte buffer.
+    ///
+    /// The buffer must start with an `SdtHeader` whose signature is made of
+    /// printable ASCII characters and whose length field matches the size of
+    /// the buffer, otherwise `INVALID_PARAMETER` is returned without calling
+    /// the firmware.
+    ///
+    /// The firmware installs a copy of the table, which it may patch, for
+    /// example to fix the checksum. The buffer is only borrowed for the
+    /// duration of the call and is never referenced afterwards, even while
+    /// the table stays installed.
+    pub fn install_bytes(&self, table: &[u8]) -> Result<TableKey> {
+        if !is_valid_table(table) {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        unsafe { self.install_acpi_table(table.as_ptr().cast(), table.len()) }
+    }
+
+    /// Install an ACPI table held in a byte buffer, checking that it has the
+    /// expected signature, such as `*b"SSDT"`.
+    ///
+    /// Fails with `INVALID_PARAMETER` on a mismatch, and otherwise behaves
+    /// like `install_bytes`.
+    pub fn install_signed(&self, expected: [u8; 4], table: &[u8]) -> Result<TableKey> {
+        if table.get(..4) != Some(&expected[..]) {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        self.install_bytes(table)
+    }
+
+    /// Install an ACPI table held in an owned buffer, such as the output of
+    /// `SsdtBuilder::build`.
+    ///
+    /// This is `install_bytes`, except that the buffer is freed once the
+    /// firmware has taken its copy, whether the installation succeeded or
+    /// not.
+    #[cfg(feature = "exts")]
+    pub fn install_owned(&self, table: Vec<u8>) -> Result<TableKey> {
+        self.install_bytes(&table)
+    }
+
+    /// Install a set of ACPI tables held in byte buffers, all or nothing.
+    ///
+    /// Each table is installed like with `install_bytes`, in order. If one
+    /// of them fails, the tables installed so far are uninstalled, most
+    /// recent first, and the error of the failed installation is returned.
+    /// Failures to uninstall are logged, since there is nothing more that
+    /// can be done about them. Warnings reported while installing the
+    /// tables are merged as with `Completion::with_status`.
+    #[cfg(feature = "exts")]
+    pub fn install_all(&self, tables: &[&[u8]]) -> Result<Vec<TableKey>> {
+        let mut status = Status::SUCCESS;
+        let mut keys = Vec::with_capacity(tables.len());
+        for table in tables {
+            match self.install_bytes(table) {
+                Ok(completion) => {
+                    let (warning, key) = completion.split();
+                    status = Completion::new(status, ()).with_status(warning).status();
+                    keys.push(key);
+                }
+                Err(error) => {
+                    while let Some(key) = keys.pop() {
+                        if let Err(error) = unsafe { self.uninstall_acpi_table(key) } {
+                            warn!("Failed to uninstall ACPI table {:?}: {:?}", error.data(), error.status());
+                        }
+                    }
+                    return Err(error);
+                }
+            }
+        }
+        status.into_with_val(|| keys)
+    }
+
+    /// Replace the installed table with the given signature, such as one
+    /// installed by the platform, with a new one.
+    ///
+    /// The existing table is found through `sdt` and uninstalled, then the
+    /// new table is installed like with `install_signed`. If that fails, a
+    /// copy of the old table is installed again before the error is
+    /// returned; failing to do so is logged. The key of the reinstalled
+    /// table is not returned, it can be looked up again through `sdt`.
+    /// Fails with `NOT_FOUND` if no table has the signature, and with
+    /// `INVALID_PARAMETER` without uninstalling anything if the new table
+    /// is malformed or does not have the signature.
+    ///
+    /// # Safety
+    ///
+    /// The firmware frees the old table once it is uninstalled, so no slice
+    /// returned by `sdt` may be used after this call.
+    #[cfg(feature = "exts")]
+    pub unsafe fn replace_table(&self, sdt: &AcpiSdt, signature: [u8; 4], new_table: &[u8]) -> Result<TableKey> {
+        if new_table.get(..4) != Some(&signature[..]) || !is_valid_table(new_table) {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        let mut status = Status::SUCCESS;
+        let (old_table, old_key) = crate::try_completion!(status, sdt.find(signature))
+            .ok_or(Status::NOT_FOUND)?;
+        // The firmware frees the old table once it is uninstalled
+        let old_table = old_table.to_vec();
+        crate::try_completion!(status, self.uninstall_acpi_table(old_key).map_err(|error| error.status()));
+        match self.install_bytes(new_table) {
+            Ok(completion) => {
+                let (warning, key) = completion.split();
+                Ok(Completion::new(status, key).with_status(warning))
+            }
+            Err(error) => {
+                if let Err(restore) = self.install_bytes(&old_table) {
+                    warn!("Failed to reinstall the replaced ACPI table: {:?}", restore.status());
+                }
+                Err(error)
+            }
+        }
+    }
+}
+
+/// Size of the header shared by all system description tables
+const SDT_HEADER_SIZE: usize = core::mem::size_of::<SdtHeader>();
+
+/// Check that a buffer holds a table `install_bytes` accepts
+fn is_valid_table(table: &[u8]) -> bool {
+    table.len() >= SDT_HEADER_SIZE
+        && table_length(table) == Some(table.len())
+        && table[..4].iter().all(u8::is_ascii_graphic)
+}
+
+/// Length of a table, as recorded in its header
+fn table_length(table: &[u8]) -> Option<usize> {
+    let length = table.get(4..8)?;
+    usize::try_from(u32::from_le_bytes([length[0], length[1], length[2], length[3]])).ok()
+}
+
+/// Header shared by all ACPI system description tables.
+#[repr(C, packed)]
+#[derive(Clone, Copy)]
+pub struct SdtHeader {
+    /// Signature identifying the kind of table, such as `SSDT`
+    pub signature: [u8; 4],
+    /// Length of the table in bytes, including this header
+    pub length: u32,
+    /// Revision of the table structure
+    pub revision: u8,
+    /// Byte making the sum of all the bytes of the table zero
+    pub checksum: u8,
+    /// OEM identifier
+    pub oem_id: [u8; 6],
+    /// OEM identifier of this particular table
+    pub oem_table_id: [u8; 8],
+    /// OEM revision of the table
+    pub oem_revision: u32,
+    /// Vendor identifier of the tool which created the table
+    pub creator_id: [u8; 4],
+    /// Revision of the tool which created the table
+    pub creator_revision: u32,
+}
+
+impl SdtHeader {
+    /// Recompute the checksum of a table after it was modified in place.
+    ///
+    /// The checksum covers the whole table, whose length is read from its
+    /// header. Fails with `INVALID_PARAMETER` if the buffer is too short to
+    /// hold a header or if that length does not match the buffer size.
+    pub fn update_checksum(table: &mut [u8]) -> Result {
+        if table.len() < SDT_HEADER_SIZE || table_length(table) != Some(table.len()) {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        table[CHECKSUM_OFFSET] = 0;
+        let sum = table.iter().fold(0u8, |sum, &byte| sum.wrapping_add(byte));
+        table[CHECKSUM_OFFSET] = 0u8.wrapping_sub(sum);
+        Ok(().into())
+    }
+}
+
+/// Offset of the checksum in the header
+const CHECKSUM_OFFSET: usize = 9;
+
+/// Builds a Secondary System Description Table around an AML blob.
+///
+/// The resulting buffer can be handed to `AcpiTable::install_bytes`.
+#[cfg(feature = "exts")]
+pub struct SsdtBuilder<'a> {
+    aml: &'a [u8],
+    revision: u8,
+    oem_id: [u8; 6],
+    oem_table_id: [u8; 8],
+    oem_revision: u32,
+    creator_id: [u8; 4],
+    creator_revision: u32
+}
+
+#[cfg(feature = "exts")]
+impl<'a> SsdtBuilder<'a> {
+    /// Start building a table holding the given AML definition blocks.
+    pub fn new(aml: &'a [u8]) -> Self {
+        SsdtBuilder {
+            aml,
+            revision: 2,
+            oem_id: *b"UEFIRS",
+            oem_table_id: *b"UEFIRS  ",
+            oem_revision: 1,
+            creator_id: *b"UEFI",
+            creator_revision: 0
+        }
+    }
+
+    /// Set the revision of the table, which is 2 by default so that AML
+    /// integers are 64-bit wide.
+    pub fn revision(mut self, revision: u8) -> Self {
+        self.revision = revision;
+        self
+    }
+
+    /// Set the OEM identifiers and revision of the table.
+    pub fn oem(mut self, oem_id: [u8; 6], oem_table_id: [u8; 8], oem_revision: u32) -> Self {
+        self.oem_id = oem_id;
+        self.oem_table_id = oem_table_id;
+        self.oem_revision = oem_revision;
+        self
+    }
+
+    /// Set the identifier and revision of the tool creating the table.
+    pub fn creator(mut self, creator_id: [u8; 4], creator_revision: u32) -> Self {
+        self.creator_id = creator_id;
+        self.creator_revision = creator_revision;
+        self
+    }
+
+    /// Produce the table, with its length and checksum filled in.
+    ///
+    /// Fails with `BAD_BUFFER_SIZE` if the table would not fit in the
+    /// 32-bit length field of its header.
+    pub fn build(&self) -> Result<Vec<u8>> {
+        let length = match u32::try_from(SDT_HEADER_SIZE + self.aml.len()) {
+            Ok(length) => length,
+            Err(_) => return Err(Status::BAD_BUFFER_SIZE.into())
+        };
+        let mut table = Vec::with_capacity(length as usize);
+        table.extend_from_slice(b"SSDT");
+        table.extend_from_slice(&length.to_le_bytes());
+        table.push(self.revision);
+        // Checksum is computed once the whole table is known
+        table.push(0);
+        table.extend_from_slice(&self.oem_id);
+        table.extend_from_slice(&self.oem_table_id);
+        table.extend_from_slice(&self.oem_revision.to_le_bytes());
+        table.extend_from_slice(&self.creator_id);
+        table.extend_from_slice(&self.creator_revision.to_le_bytes());
+        table.extend_from_slice(self.aml);
+        SdtHeader::update_checksum(&mut table)
+            .map_inner(|_| table)
+    }
+}
+
+/// Key identifying an installed ACPI table.
+///
+/// Keys can only be obtained from `AcpiTable::install_acpi_table` or from
+/// the enumeration functions of `AcpiSdt`, and are consumed by
+/// `AcpiTable::uninstall_acpi_table`.
+#[derive(Debug, Eq, PartialEq)]
+#[repr(transparent)]
+pub struct TableKey(usize);
+
+impl TableKey {
+    pub(crate) fn new(key: usize) -> TableKey {
+        TableKey(key)
     }
 
-    pub unsafe fn uninstall_acpi_table(&self, table_key: usize) -> Result {
-        (self.uninstall_table)(self, table_key)
-            .into()
+    /// The raw key returned by the firmware.
+    pub fn value(&self) -> usize {
+        self.0
     }
 }
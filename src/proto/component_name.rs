@@ -1,7 +1,11 @@
-use crate::{unsafe_guid, Handle, Status};
+use crate::{unsafe_guid, Handle, Result, ResultExt, Status};
 use crate::proto::Protocol;
-use crate::data_types::{Char16, Char8};
-use core::ptr::NonNull;
+use crate::data_types::{CStr16, CStr8, Char16, Char8};
+use core::ptr::{self, NonNull};
+use core::str;
+
+#[cfg(feature = "exts")]
+use alloc_api::{collections::BTreeMap, string::String, vec::Vec};
 
 type GetDriverName2Fn =
     extern "efiapi" fn(this: &ComponentName2, language: *const Char8, driver_name: *mut *const Char16) -> Status;
@@ -28,6 +32,216 @@ impl ComponentName2 {
             supported_languages
         }
     }
+
+    /// Languages supported by this component, as RFC 4646 language tags
+    pub fn supported_languages(&self) -> impl Iterator<Item = &str> + '_ {
+        let languages = if self.supported_languages.is_null() {
+            &[][..]
+        } else {
+            unsafe { CStr8::from_ptr(self.supported_languages) }.to_bytes()
+        };
+        languages
+            .split(|&c| c == b';')
+            .filter(|tag| !tag.is_empty())
+            .filter_map(|tag| str::from_utf8(tag).ok())
+    }
+
+    /// Retrieve the name of the driver in the specified language
+    ///
+    /// Some firmware reports success without returning a name, which is
+    /// reported as a `NOT_FOUND` error.
+    pub fn driver_name(&self, language: &CStr8) -> Result<&CStr16> {
+        let mut driver_name = ptr::null();
+        let status = (self.get_driver_name)(self, language.as_ptr(), &mut driver_name);
+        if !status.is_error() && driver_name.is_null() {
+            return Err(Status::NOT_FOUND.into());
+        }
+        status.into_with_val(|| unsafe { CStr16::from_ptr(driver_name) })
+    }
+
+    /// Copy the name of the driver in the specified language into a buffer
+    /// provided by the caller, returning the number of code units copied
+    ///
+    /// The name is not NUL-terminated in the buffer. Fails with
+    /// `BUFFER_TOO_SMALL` if it does not fit, leaving the buffer untouched.
+    pub fn copy_driver_name(&self, language: &CStr8, out: &mut [u16]) -> Result<usize> {
+        let mut status = Status::SUCCESS;
+        let name = crate::try_completion!(status, self.driver_name(language)).to_u16_slice();
+        if name.len() > out.len() {
+            return Err(Status::BUFFER_TOO_SMALL.into());
+        }
+        out[..name.len()].copy_from_slice(name);
+        status.into_with_val(|| name.len())
+    }
+
+    /// Retrieve the name of the driver in the supported language which best
+    /// matches the requested one
+    ///
+    /// Language tags are compared after normalization, so `en-us` and
+    /// `en_US` find a driver advertising `en-US`. If no supported tag is an
+    /// exact match, the first one with the same primary language subtag is
+    /// used, as with BCP 47 lookup. The firmware is then queried with the tag
+    /// as it spells it, which is returned alongside the name. Fails with
+    /// `UNSUPPORTED` if no supported language matches.
+    pub fn best_name(&self, language: &str) -> Result<(LanguageTag, &CStr16)> {
+        let requested = LanguageTag::normalize(language).ok_or(Status::INVALID_PARAMETER)?;
+        let matches = |exact: bool| {
+            self.supported_languages().find(|&tag| match LanguageTag::normalize(tag) {
+                Some(tag) if exact => tag.as_str() == requested.as_str(),
+                Some(tag) => tag.primary_subtag() == requested.primary_subtag(),
+                None => false
+            })
+        };
+        let tag = matches(true)
+            .or_else(|| matches(false))
+            .and_then(LanguageTag::new)
+            .ok_or(Status::UNSUPPORTED)?;
+        self.driver_name(tag.as_cstr8())
+            .map_inner(|name| (tag, name))
+    }
+
+    /// Iterate over the name of the driver in every supported language
+    ///
+    /// Languages for which the name cannot be retrieved are skipped. The
+    /// language tags are yielded as NUL-terminated copies, since the list
+    /// of supported languages is a single string which cannot be borrowed
+    /// piecewise as C strings.
+    pub fn all_driver_names(&self) -> impl Iterator<Item = (LanguageTag, &CStr16)> + '_ {
+        self.supported_languages()
+            .filter_map(LanguageTag::new)
+            .filter_map(move |tag| {
+                let name = self.driver_name(tag.as_cstr8()).ok()?.log();
+                Some((tag, name))
+            })
+    }
+
+    /// Collect the name of the driver in every supported language
+    #[cfg(feature = "exts")]
+    pub fn driver_name_map(&self) -> BTreeMap<String, String> {
+        self.all_driver_names()
+            .map(|(tag, name)| {
+                (String::from(tag.as_str()), name.chars().collect())
+            })
+            .collect()
+    }
+}
+
+/// Names of a set of drivers, fetched once in a preferred language
+///
+/// Each name is retrieved with `ComponentName2::best_name` when the cache is
+/// built, so displaying them afterwards does not call into the firmware.
+#[cfg(feature = "exts")]
+pub struct DriverNameCache {
+    names: Vec<Option<String>>,
+}
+
+#[cfg(feature = "exts")]
+impl DriverNameCache {
+    /// Fetch the name of every driver in the language which best matches
+    /// the preferred one.
+    ///
+    /// Drivers whose name cannot be retrieved in any matching language get
+    /// no name, and the warnings encountered are logged.
+    pub fn new(drivers: &[&ComponentName2], language: &str) -> DriverNameCache {
+        let names = drivers
+            .iter()
+            .map(|driver| {
+                let (_, name) = driver.best_name(language).ok()?.log();
+                Some(name.chars().collect())
+            })
+            .collect();
+        DriverNameCache { names }
+    }
+
+    /// Name of the driver at the given index of the slice the cache was
+    /// built from, if it could be retrieved.
+    pub fn get(&self, index: usize) -> Option<&str> {
+        self.names.get(index)?.as_deref()
+    }
+}
+
+/// Longest language tag which can be stored in a `LanguageTag`
+const MAX_LANGUAGE_TAG_LEN: usize = 63;
+
+/// NUL-terminated copy of a language tag
+///
+/// Language tags are short, so this does not require memory allocation.
+#[derive(Clone, Copy)]
+pub struct LanguageTag {
+    len: usize,
+    buffer: [u8; MAX_LANGUAGE_TAG_LEN + 1],
+}
+
+impl LanguageTag {
+    /// Copy a language tag, failing if it is too long or not ASCII
+    pub fn new(tag: &str) -> Option<LanguageTag> {
+        if tag.len() > MAX_LANGUAGE_TAG_LEN || !tag.bytes().all(|c| c.is_ascii_graphic()) {
+            return None;
+        }
+        let mut buffer = [0; MAX_LANGUAGE_TAG_LEN + 1];
+        buffer[..tag.len()].copy_from_slice(tag.as_bytes());
+        Some(LanguageTag {
+            len: tag.len(),
+            buffer
+        })
+    }
+
+    /// Copy a language tag in the canonical BCP 47 form
+    ///
+    /// Underscores are accepted as subtag separators. The primary language
+    /// subtag is lowercased, four-letter script subtags are titlecased,
+    /// two-letter region subtags are uppercased and other subtags are
+    /// lowercased, so that `EN_us` becomes `en-US`.
+    pub fn normalize(tag: &str) -> Option<LanguageTag> {
+        let mut tag = LanguageTag::new(tag.trim())?;
+        let len = tag.len;
+        let mut private = false;
+        for c in &mut tag.buffer[..len] {
+            if *c == b'_' {
+                *c = b'-';
+            }
+        }
+        for (index, subtag) in tag.buffer[..len].split_mut(|&c| c == b'-').enumerate() {
+            subtag.make_ascii_lowercase();
+            // Subtags following a singleton, such as `x`, have no
+            // canonical casing
+            if index == 0 || private {
+                private |= subtag.len() == 1;
+                continue;
+            }
+            let alphabetic = subtag.iter().all(u8::is_ascii_alphabetic);
+            match subtag.len() {
+                1 => private = true,
+                2 if alphabetic => subtag.make_ascii_uppercase(),
+                4 if alphabetic => subtag[0].make_ascii_uppercase(),
+                _ => {}
+            }
+        }
+        Some(tag)
+    }
+
+    /// Primary language subtag, such as `en` in `en-US`
+    pub fn primary_subtag(&self) -> &str {
+        let tag = self.as_str();
+        tag.split(|c| c == '-' || c == '_').next().unwrap_or(tag)
+    }
+
+    /// Access the language tag as a string slice
+    pub fn as_str(&self) -> &str {
+        // Only ASCII characters are ever stored
+        unsafe { str::from_utf8_unchecked(&self.buffer[..self.len]) }
+    }
+
+    /// Access the language tag as a C string, for use with firmware calls
+    pub fn as_cstr8(&self) -> &CStr8 {
+        unsafe { CStr8::from_bytes_with_nul_unchecked(&self.buffer[..=self.len]) }
+    }
+}
+
+impl core::fmt::Debug for LanguageTag {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(f, "LanguageTag({:?})", self.as_str())
+    }
 }
 
 type GetDriverNameFn =
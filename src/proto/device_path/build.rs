@@ -0,0 +1,97 @@
+//! Construction of owned device paths
+
+use super::{DevicePath, DeviceSubType, DeviceType};
+use alloc_api::{boxed::Box, vec::Vec};
+use core::convert::TryFrom;
+
+/// Size of the header shared by all device path nodes
+const NODE_HEADER_SIZE: usize = 4;
+
+/// Builds a device path by appending nodes to an existing one.
+///
+/// A bus driver typically starts from the device path of the controller
+/// it manages and appends a node describing each child it creates.
+#[derive(Debug, Default)]
+pub struct DevicePathBuilder {
+    buffer: Vec<u8>
+}
+
+impl DevicePathBuilder {
+    /// Create a builder for an empty device path.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a builder initialized with all the nodes of `base`, except
+    /// for its terminating End node.
+    pub fn from_base(base: &DevicePath) -> Self {
+        let mut buffer = Vec::new();
+        let mut node = base as *const DevicePath as *const u8;
+        loop {
+            let (device_type, sub_type, length) = unsafe {
+                (*node, *node.add(1), u16::from_le_bytes([*node.add(2), *node.add(3)]) as usize)
+            };
+            if device_type == DeviceType::End as u8 && sub_type == DeviceSubType::EndEntire as u8 {
+                break;
+            }
+            if length < NODE_HEADER_SIZE {
+                // A malformed node would never let us reach the End node
+                break;
+            }
+            unsafe {
+                buffer.extend_from_slice(core::slice::from_raw_parts(node, length));
+                node = node.add(length);
+            }
+        }
+        DevicePathBuilder {
+            buffer
+        }
+    }
+
+    /// Append a node of the given type and sub-type, followed by its
+    /// type-specific data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the node would not fit in the 16-bit node length.
+    pub fn push_node(&mut self, device_type: DeviceType, sub_type: u8, data: &[u8]) -> &mut Self {
+        let length = u16::try_from(NODE_HEADER_SIZE + data.len())
+            .expect("Device path node data is too long");
+        self.buffer.push(device_type as u8);
+        self.buffer.push(sub_type);
+        self.buffer.extend_from_slice(&length.to_le_bytes());
+        self.buffer.extend_from_slice(data);
+        self
+    }
+
+    /// Terminate the device path with an End Entire node.
+    pub fn finalize(mut self) -> DevicePathBuf {
+        self.buffer.push(DeviceType::End as u8);
+        self.buffer.push(DeviceSubType::EndEntire as u8);
+        self.buffer.extend_from_slice(&(NODE_HEADER_SIZE as u16).to_le_bytes());
+        DevicePathBuf {
+            buffer: self.buffer.into_boxed_slice()
+        }
+    }
+}
+
+/// An owned device path, terminated by an End node.
+///
+/// The buffer must stay alive for as long as the device path protocol it
+/// backs is installed on a handle.
+#[derive(Debug)]
+pub struct DevicePathBuf {
+    buffer: Box<[u8]>
+}
+
+impl DevicePathBuf {
+    /// The device path, starting from its first node.
+    pub fn as_device_path(&self) -> &DevicePath {
+        unsafe { &*(self.buffer.as_ptr() as *const DevicePath) }
+    }
+
+    /// The raw bytes of all the nodes, including the End node.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.buffer
+    }
+}
@@ -2,6 +2,11 @@
 
 use crate::{proto::Protocol, unsafe_guid};
 
+#[cfg(feature = "exts")]
+mod build;
+#[cfg(feature = "exts")]
+pub use self::build::{DevicePathBuf, DevicePathBuilder};
+
 /// Device path protocol.
 ///
 /// This can be opened on a `LoadedImage.device()` handle using the `HandleProtocol` boot service.
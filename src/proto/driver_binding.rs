@@ -1,6 +1,15 @@
-use crate::{unsafe_guid, Handle, Status};
+use crate::{unsafe_guid, Handle, Result, ResultExt, Status};
+use crate::proto::component_name::ComponentName2;
 use crate::proto::device_path::DevicePath;
+use crate::proto::driver_configuration::DriverConfiguration2;
+use crate::proto::driver_diagnostics::DriverDiagnostics2;
+use crate::proto::pci::{PciClass, PciIO};
 use crate::proto::Protocol;
+use crate::table::boot::{BootServices, OpenAttribute};
+use log::debug;
+
+#[cfg(feature = "exts")]
+use alloc_api::vec::Vec;
 
 /// Start routine
 type StartFn = extern "efiapi" fn(this: &DriverBinding, controller: Handle, remaining_path: *mut DevicePath) -> Status;
@@ -11,6 +20,19 @@ type SupportedFn = extern "efiapi" fn(this: &DriverBinding, controller: Handle,
 /// Stop routine
 type StopFn = extern "efiapi" fn(this: &DriverBinding, controller: Handle, num_child_controller: usize, child_controller: *mut Handle) -> Status;
 
+/// The Driver Binding protocol, through which the firmware connects a
+/// driver to the controllers it manages.
+///
+/// # Panics
+///
+/// Routines must never unwind into the firmware. This crate is `no_std`,
+/// so panics cannot be caught and turned into a status such as `ABORTED`;
+/// instead, the UEFI targets build with `panic = "abort"` and the panic
+/// handler, like the one of `uefi-services`, never returns. Older
+/// compilers do not stop an unwind at an `extern "efiapi"` boundary, so
+/// the trampolines installed by `with_tracing` abort themselves should
+/// the wrapped routine unwind when a panic strategy with unwinding is
+/// used anyway.
 #[repr(C)]
 #[unsafe_guid("18a031ab-b443-4d1a-a5c0-0c09261e9f71")]
 #[derive(Protocol)]
@@ -38,4 +60,251 @@ impl DriverBinding {
     pub fn driver_handle(&self) -> Handle {
         self.driver_binding_handle
     }
+
+    /// Check that this driver binding was set up and installed correctly.
+    ///
+    /// Fails with `INVALID_PARAMETER` if the image handle or the driver
+    /// binding handle is null, and with `NOT_FOUND` if this interface is not
+    /// the driver binding installed on the driver binding handle, which is
+    /// the case when it was installed on another handle by mistake.
+    pub fn validate(&self, bt: &BootServices) -> Result {
+        if self.image_handle.is_null() || self.driver_binding_handle.is_null() {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        let installed = bt.handle_protocol::<DriverBinding>(self.driver_binding_handle)
+            .map_err(|_| Status::NOT_FOUND)?;
+        let (status, installed) = installed.split();
+        if !core::ptr::eq(installed.get(), self) {
+            return Err(Status::NOT_FOUND.into());
+        }
+        status.into()
+    }
+
+    /// Find the child controllers this driver created for a controller.
+    ///
+    /// Bus drivers open the protocol `P` of the controller, such as `PciIO`,
+    /// with the `BY_CHILD` attribute on behalf of each child they create.
+    /// The children are found in the open information of that protocol,
+    /// which is what a stop routine needs to destroy exactly the children
+    /// of this driver.
+    #[cfg(feature = "exts")]
+    pub fn child_handles<P: Protocol>(&self, bt: &BootServices, controller: Handle) -> Result<Vec<Handle>> {
+        bt.open_protocol_information::<P>(controller)
+            .map_inner(|entries| {
+                entries
+                    .into_iter()
+                    .filter(|entry| entry.agent == self.driver_binding_handle)
+                    .filter(|entry| entry.attributes.contains(OpenAttribute::BY_CHILD))
+                    .map(|entry| entry.controller)
+                    .collect()
+            })
+    }
+
+    /// Implementation of a supported routine for drivers of PCI controllers
+    /// of the given base classes.
+    ///
+    /// The PCI I/O protocol is opened `BY_DRIVER` on behalf of this driver
+    /// binding, which fails if another driver manages the controller, and
+    /// closed again before returning. The status of a failed open, such as
+    /// `ALREADY_STARTED` or `ACCESS_DENIED`, is passed on to the firmware
+    /// as the specification requires. Controllers of other classes, or
+    /// whose class code cannot be read, are `UNSUPPORTED`.
+    pub fn supported_if_class(&self, bt: &BootServices, controller: Handle, classes: &[PciClass]) -> Status {
+        let pci = match bt.open_protocol::<PciIO>(controller, self.driver_binding_handle, controller, OpenAttribute::BY_DRIVER) {
+            Ok(pci) => pci.log(),
+            Err(error) => return error.status()
+        };
+        // Dropping the guard closes the protocol with the same arguments
+        match pci.with_proto(|pci| pci.class()) {
+            Ok(class_code) if classes.contains(&class_code.log().class) => Status::SUCCESS,
+            _ => Status::UNSUPPORTED
+        }
+    }
+
+    /// Borrow the list of child controllers handed to a stop routine.
+    ///
+    /// The firmware passes a null pointer when there are no children to
+    /// stop, from which a slice must not be built: an empty slice is
+    /// returned instead whenever the count is zero or the pointer is null.
+    ///
+    /// # Safety
+    ///
+    /// Unless the count is zero or the pointer is null, the pointer must
+    /// refer to `num_child_controller` handles which stay valid for `'a`,
+    /// as is the case for the arguments of a stop routine while it runs.
+    pub unsafe fn child_controllers<'a>(num_child_controller: usize, child_controller: *mut Handle) -> &'a [Handle] {
+        if num_child_controller == 0 || child_controller.is_null() {
+            &[]
+        } else {
+            core::slice::from_raw_parts(child_controller, num_child_controller)
+        }
+    }
+
+    /// Wrap a driver binding so that every call made by the firmware
+    /// into `supported`, `start` and `stop` gets logged together with
+    /// the controller handle and the resulting status.
+    ///
+    /// The returned object must be kept at a fixed address for as long
+    /// as its `binding()` is installed, just like a plain `DriverBinding`.
+    pub fn with_tracing(inner: DriverBinding) -> TracingDriverBinding {
+        TracingDriverBinding {
+            binding: DriverBinding {
+                supported: traced_supported,
+                start: traced_start,
+                stop: traced_stop,
+                version: inner.version,
+                image_handle: inner.image_handle,
+                driver_binding_handle: inner.driver_binding_handle
+            },
+            inner
+        }
+    }
+}
+
+/// Driver binding instrumented with logging, see `DriverBinding::with_tracing`.
+///
+/// The instrumented binding must stay the first field: the trampolines
+/// recover the whole structure from the `this` pointer handed to them
+/// by the firmware.
+#[repr(C)]
+pub struct TracingDriverBinding {
+    binding: DriverBinding,
+    inner: DriverBinding
+}
+
+impl TracingDriverBinding {
+    /// Protocol interface which should be installed on the driver handle.
+    pub fn binding(&self) -> &DriverBinding {
+        &self.binding
+    }
+
+    /// Driver binding being traced.
+    pub fn inner(&self) -> &DriverBinding {
+        &self.inner
+    }
+
+    /// Recover the tracing wrapper from the interface installed in firmware.
+    ///
+    /// SAFETY: `this` must be the `binding` field of a `TracingDriverBinding`,
+    /// which holds as long as the trampolines below are only ever installed
+    /// by `DriverBinding::with_tracing`.
+    unsafe fn from_binding(this: &DriverBinding) -> &TracingDriverBinding {
+        &*(this as *const DriverBinding as *const TracingDriverBinding)
+    }
+}
+
+/// Guard armed for the duration of a call into the traced driver binding.
+///
+/// It is forgotten once the call returns, so it is only dropped if the
+/// routine unwinds. Panicking again while unwinding aborts, which keeps
+/// the unwind from reaching the firmware.
+struct AbortOnUnwind;
+
+impl Drop for AbortOnUnwind {
+    fn drop(&mut self) {
+        panic!("DriverBinding routine unwound into the firmware, aborting");
+    }
+}
+
+extern "efiapi" fn traced_supported(this: &DriverBinding, controller: Handle, remaining_path: *mut DevicePath) -> Status {
+    let tracing = unsafe { TracingDriverBinding::from_binding(this) };
+    let guard = AbortOnUnwind;
+    let status = (tracing.inner.supported)(&tracing.inner, controller, remaining_path);
+    core::mem::forget(guard);
+    debug!("DriverBinding::supported({:?}, remaining_path: {:?}) -> {:?}", controller, remaining_path, status);
+    status
+}
+
+extern "efiapi" fn traced_start(this: &DriverBinding, controller: Handle, remaining_path: *mut DevicePath) -> Status {
+    let tracing = unsafe { TracingDriverBinding::from_binding(this) };
+    let guard = AbortOnUnwind;
+    let status = (tracing.inner.start)(&tracing.inner, controller, remaining_path);
+    core::mem::forget(guard);
+    debug!("DriverBinding::start({:?}, remaining_path: {:?}) -> {:?}", controller, remaining_path, status);
+    status
+}
+
+extern "efiapi" fn traced_stop(this: &DriverBinding, controller: Handle, num_child_controller: usize, child_controller: *mut Handle) -> Status {
+    let tracing = unsafe { TracingDriverBinding::from_binding(this) };
+    let guard = AbortOnUnwind;
+    let status = (tracing.inner.stop)(&tracing.inner, controller, num_child_controller, child_controller);
+    core::mem::forget(guard);
+    debug!("DriverBinding::stop({:?}, children: {}) -> {:?}", controller, num_child_controller, status);
+    status
+}
+
+/// Driver binding together with the companion protocols of the driver.
+///
+/// The protocols are installed on the driver binding handle at once with
+/// `InstallMultipleProtocolInterfaces`, so either all of them or none of
+/// them end up installed. They must stay at a fixed address for as long as
+/// they are installed.
+pub struct DriverBundle<'a> {
+    binding: &'a DriverBinding,
+    component_name2: Option<&'a ComponentName2>,
+    diagnostics2: Option<&'a DriverDiagnostics2>,
+    configuration2: Option<&'a DriverConfiguration2>
+}
+
+impl<'a> DriverBundle<'a> {
+    /// Create a bundle holding only the driver binding.
+    pub fn new(binding: &'a DriverBinding) -> DriverBundle<'a> {
+        DriverBundle {
+            binding,
+            component_name2: None,
+            diagnostics2: None,
+            configuration2: None
+        }
+    }
+
+    /// Also install the ComponentName2 protocol.
+    pub fn with_component_name2(mut self, component_name2: &'a ComponentName2) -> DriverBundle<'a> {
+        self.component_name2 = Some(component_name2);
+        self
+    }
+
+    /// Also install the DriverDiagnostics2 protocol.
+    pub fn with_diagnostics2(mut self, diagnostics2: &'a DriverDiagnostics2) -> DriverBundle<'a> {
+        self.diagnostics2 = Some(diagnostics2);
+        self
+    }
+
+    /// Also install the DriverConfiguration2 protocol.
+    pub fn with_configuration2(mut self, configuration2: &'a DriverConfiguration2) -> DriverBundle<'a> {
+        self.configuration2 = Some(configuration2);
+        self
+    }
+
+    /// Install all the protocols of the bundle on the driver binding handle.
+    pub fn install(&self, bt: &'a BootServices) -> Result {
+        let handle = Some(self.binding.driver_handle());
+        let binding = self.binding;
+        match (self.component_name2, self.diagnostics2, self.configuration2) {
+            (None, None, None) => bt.install_multiple_protocol_interfaces1(handle, binding),
+            (Some(name), None, None) => bt.install_multiple_protocol_interfaces2(handle, binding, name),
+            (None, Some(diag), None) => bt.install_multiple_protocol_interfaces2(handle, binding, diag),
+            (None, None, Some(config)) => bt.install_multiple_protocol_interfaces2(handle, binding, config),
+            (Some(name), Some(diag), None) => bt.install_multiple_protocol_interfaces3(handle, binding, name, diag),
+            (Some(name), None, Some(config)) => bt.install_multiple_protocol_interfaces3(handle, binding, name, config),
+            (None, Some(diag), Some(config)) => bt.install_multiple_protocol_interfaces3(handle, binding, diag, config),
+            (Some(name), Some(diag), Some(config)) => bt.install_multiple_protocol_interfaces4(handle, binding, name, diag, config),
+        }
+        .map_inner(|_| ())
+    }
+
+    /// Uninstall all the protocols of the bundle from the driver binding handle.
+    pub fn uninstall(&self, bt: &'a BootServices) -> Result {
+        let handle = self.binding.driver_handle();
+        let binding = self.binding;
+        match (self.component_name2, self.diagnostics2, self.configuration2) {
+            (None, None, None) => bt.uninstall_multiple_protocol_interfaces1(handle, binding),
+            (Some(name), None, None) => bt.uninstall_multiple_protocol_interfaces2(handle, binding, name),
+            (None, Some(diag), None) => bt.uninstall_multiple_protocol_interfaces2(handle, binding, diag),
+            (None, None, Some(config)) => bt.uninstall_multiple_protocol_interfaces2(handle, binding, config),
+            (Some(name), Some(diag), None) => bt.uninstall_multiple_protocol_interfaces3(handle, binding, name, diag),
+            (Some(name), None, Some(config)) => bt.uninstall_multiple_protocol_interfaces3(handle, binding, name, config),
+            (None, Some(diag), Some(config)) => bt.uninstall_multiple_protocol_interfaces3(handle, binding, diag, config),
+            (Some(name), Some(diag), Some(config)) => bt.uninstall_multiple_protocol_interfaces4(handle, binding, name, diag, config),
+        }
+    }
 }
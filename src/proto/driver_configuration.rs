@@ -0,0 +1,54 @@
+//! `DriverConfiguration2` protocol.
+
+use crate::{unsafe_guid, Handle, Status};
+use crate::proto::Protocol;
+use crate::data_types::Char8;
+use core::ptr::NonNull;
+
+type SetOptions2Fn =
+    extern "efiapi" fn(this: &DriverConfiguration2, controller: Handle, child: Option<NonNull<Handle>>, language: *const Char8, action_required: *mut ActionRequired) -> Status;
+
+type OptionsValid2Fn =
+    extern "efiapi" fn(this: &DriverConfiguration2, controller: Handle, child: Option<NonNull<Handle>>) -> Status;
+
+type ForceDefaults2Fn =
+    extern "efiapi" fn(this: &DriverConfiguration2, controller: Handle, child: Option<NonNull<Handle>>, default_type: u32, action_required: *mut ActionRequired) -> Status;
+
+/// Wrapper for DriverConfiguration2 protocol which allows UEFI drivers
+/// to configure the controllers they manage.
+#[repr(C)]
+#[unsafe_guid("bfd7dc1d-24f1-40d9-82e7-2e09bb6b4ebe")]
+#[derive(Protocol)]
+pub struct DriverConfiguration2 {
+    set_options: SetOptions2Fn,
+    options_valid: OptionsValid2Fn,
+    force_defaults: ForceDefaults2Fn,
+    supported_languages: *const Char8
+}
+
+impl DriverConfiguration2 {
+    /// Build the protocol interface from the functions implemented by the
+    /// driver and its semicolon-separated list of language tags.
+    pub fn new(set_options: SetOptions2Fn, options_valid: OptionsValid2Fn, force_defaults: ForceDefaults2Fn, supported_languages: *const Char8) -> DriverConfiguration2 {
+        DriverConfiguration2 {
+            set_options,
+            options_valid,
+            force_defaults,
+            supported_languages
+        }
+    }
+}
+
+newtype_enum! {
+    /// Action the user must take for a new configuration to take effect.
+    pub enum ActionRequired: u32 => {
+        /// No action is required
+        NONE               = 0,
+        /// The controller must be stopped
+        STOP_CONTROLLER    = 1,
+        /// The controller must be restarted
+        RESTART_CONTROLLER = 2,
+        /// The platform must be restarted
+        RESTART_PLATFORM   = 3,
+    }
+}
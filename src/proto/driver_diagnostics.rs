@@ -0,0 +1,44 @@
+//! `DriverDiagnostics2` protocol.
+
+use crate::{unsafe_guid, Guid, Handle, Status};
+use crate::proto::Protocol;
+use crate::data_types::{Char16, Char8};
+use core::ptr::NonNull;
+
+type RunDiagnostics2Fn =
+    extern "efiapi" fn(this: &DriverDiagnostics2, controller: Handle, child: Option<NonNull<Handle>>, diagnostic_type: DiagnosticType, language: *const Char8, error_type: *mut *const Guid, buffer_size: *mut usize, buffer: *mut *mut Char16) -> Status;
+
+/// Wrapper for DriverDiagnostics2 protocol which allows UEFI drivers
+/// to expose diagnostics of the controllers they manage.
+#[repr(C)]
+#[unsafe_guid("4d330321-025f-4aac-90d8-5ed900173b63")]
+#[derive(Protocol)]
+pub struct DriverDiagnostics2 {
+    run_diagnostics: RunDiagnostics2Fn,
+    supported_languages: *const Char8
+}
+
+impl DriverDiagnostics2 {
+    /// Build the protocol interface from the functions implemented by the
+    /// driver and its semicolon-separated list of language tags.
+    pub fn new(run_diagnostics: RunDiagnostics2Fn, supported_languages: *const Char8) -> DriverDiagnostics2 {
+        DriverDiagnostics2 {
+            run_diagnostics,
+            supported_languages
+        }
+    }
+}
+
+newtype_enum! {
+    /// Kind of diagnostics to run on a controller.
+    pub enum DiagnosticType: u32 => {
+        /// Quick diagnostics which do not require user interaction
+        STANDARD      = 0,
+        /// Longer diagnostics which may require user interaction
+        EXTENDED      = 1,
+        /// Diagnostics meant for manufacturing tests
+        MANUFACTURING = 2,
+        /// Cancel diagnostics which are running asynchronously
+        CANCEL        = 3,
+    }
+}
@@ -36,4 +36,7 @@ pub mod shim;
 pub mod driver_binding;
 pub mod pci;
 pub mod component_name;
+pub mod acpi_sdt;
 pub mod acpi_table;
+pub mod driver_diagnostics;
+pub mod driver_configuration;
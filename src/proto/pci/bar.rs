@@ -0,0 +1,213 @@
+//! Base Address Registers.
+
+use super::{ConfigRegister, IoRegister, PciIO};
+use crate::{Result, ResultExt, Status};
+use core::convert::TryFrom;
+
+/// BAR bit indicating an I/O space region
+const BAR_IO_SPACE: u32 = 1 << 0;
+/// BAR field holding the type of a memory region
+const BAR_MEMORY_TYPE: u32 = 0x3 << 1;
+/// BAR memory type of a region decoded with 64 bit addresses
+const BAR_MEMORY_TYPE_64: u32 = 0x2 << 1;
+/// BAR bit indicating a prefetchable memory region
+const BAR_PREFETCHABLE: u32 = 1 << 3;
+/// BAR flag bits of an I/O space region
+const BAR_IO_FLAGS: u32 = 0x3;
+/// BAR flag bits of a memory region
+const BAR_MEMORY_FLAGS: u32 = 0xf;
+
+/// Command register bit enabling the decoding of I/O space regions
+const COMMAND_IO_SPACE: u16 = 1 << 0;
+/// Command register bit enabling the decoding of memory regions
+const COMMAND_MEMORY_SPACE: u16 = 1 << 1;
+
+/// Header type field of the HEADER_TYPE register, without the
+/// multi-function bit
+const HEADER_TYPE_LAYOUT: u8 = 0x7f;
+
+impl PciIO {
+    /// Read the base address assigned to a Base Address Register.
+    ///
+    /// The type flags held by the low bits are masked out. The upper half
+    /// of a 64 bit memory region is read from the following register.
+    /// Fails with `INVALID_PARAMETER` for `PASS_THROUGH_BAR`, and with
+    /// `DEVICE_ERROR` if the last register claims to be a 64 bit one.
+    pub fn bar_base(&self, bar: IoRegister) -> Result<u64> {
+        self.bar_info(bar)
+            .map_inner(|info| info.base)
+    }
+
+    /// Read and decode a Base Address Register.
+    ///
+    /// Fails like `bar_base`.
+    pub fn bar_info(&self, bar: IoRegister) -> Result<BarInfo> {
+        if bar.0 > IoRegister::R5.0 {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        let offset = ConfigRegister::BAR0.0 + 4 * u32::from(bar.0);
+        let mut status = Status::SUCCESS;
+        let low = crate::try_completion!(status, self.read_config_single::<u32>(offset));
+        if low & BAR_IO_SPACE != 0 {
+            return status.into_with_val(|| BarInfo {
+                bar,
+                base: u64::from(low & !BAR_IO_FLAGS),
+                io: true,
+                prefetchable: false,
+                is_64bit: false
+            });
+        }
+        let is_64bit = low & BAR_MEMORY_TYPE == BAR_MEMORY_TYPE_64;
+        let high = if is_64bit {
+            if bar == IoRegister::R5 {
+                return Err(Status::DEVICE_ERROR.into());
+            }
+            crate::try_completion!(status, self.read_config_single::<u32>(offset + 4))
+        } else {
+            0
+        };
+        status.into_with_val(|| BarInfo {
+            bar,
+            base: (u64::from(high) << 32) | u64::from(low & !BAR_MEMORY_FLAGS),
+            io: false,
+            prefetchable: low & BAR_PREFETCHABLE != 0,
+            is_64bit
+        })
+    }
+
+    /// Size the region decoded by a Base Address Register.
+    ///
+    /// The register is written with all ones and the address bits the
+    /// controller keeps cleared give the size of the region, which is zero
+    /// for unimplemented registers. Decoding is disabled in the meantime,
+    /// and the register and the command register are then restored, so
+    /// the controller must not be in use while it is sized. Fails like
+    /// `bar_base`.
+    pub fn bar_size(&self, bar: IoRegister) -> Result<u64> {
+        let mut status = Status::SUCCESS;
+        let info = crate::try_completion!(status, self.bar_info(bar));
+        let offset = ConfigRegister::BAR0.0 + 4 * u32::from(bar.0);
+        let count = if info.is_64bit { 2 } else { 1 };
+        let mut saved = [0u32; 2];
+        crate::try_completion!(status, self.read_config(offset, &mut saved[..count]));
+        let command = crate::try_completion!(status, self.read_config_single::<u16>(ConfigRegister::COMMAND.0));
+        let decoding = COMMAND_IO_SPACE | COMMAND_MEMORY_SPACE;
+        crate::try_completion!(status, self.write_config(ConfigRegister::COMMAND.0, &[command & !decoding]));
+        let mut sized = [0u32; 2];
+        let sizing = self.write_config(offset, &[!0u32; 2][..count])
+            .and_then(|written| self.read_config(offset, &mut sized[..count]).map(|read| written.and(read)));
+        // Restore the controller even if sizing failed
+        let restored = self.write_config(offset, &saved[..count])
+            .and_then(|written| self.write_config(ConfigRegister::COMMAND.0, &[command]).map(|command| written.and(command)));
+        crate::try_completion!(status, sizing);
+        crate::try_completion!(status, restored);
+        let mask = if info.io {
+            // Controllers decoding 16 bit port addresses may leave the upper
+            // half of the register cleared
+            match sized[0] & !BAR_IO_FLAGS {
+                0 => 0,
+                mask if mask >> 16 == 0 => u64::from(mask | 0xffff_0000) | 0xffff_ffff_0000_0000,
+                mask => u64::from(mask) | 0xffff_ffff_0000_0000
+            }
+        } else if info.is_64bit {
+            (u64::from(sized[1]) << 32) | u64::from(sized[0] & !BAR_MEMORY_FLAGS)
+        } else {
+            match sized[0] & !BAR_MEMORY_FLAGS {
+                0 => 0,
+                mask => u64::from(mask) | 0xffff_ffff_0000_0000
+            }
+        };
+        let size = if mask == 0 { 0 } else { (!mask).wrapping_add(1) };
+        status.into_with_val(|| size)
+    }
+
+    /// Get a pointer to the start of a memory region, for drivers which
+    /// access registers directly rather than through `read_mem` and
+    /// `write_mem`.
+    ///
+    /// The pointer is the base address assigned to the register, as read
+    /// by `bar_base`. The bus addresses of the region are taken to be the
+    /// processor addresses, which holds on the platforms where the root
+    /// bridge does not translate memory accesses. Fails like `bar_base`,
+    /// and with `UNSUPPORTED` for I/O space regions, regions which have no
+    /// address assigned and regions beyond the processor address space.
+    ///
+    /// # Safety
+    ///
+    /// Whatever is done with the pointer is not synchronized with the
+    /// accesses made through the protocol. Accesses must be volatile, stay
+    /// within the region and occur only while memory decoding is enabled.
+    pub unsafe fn mmio_base(&self, bar: IoRegister) -> Result<*mut u8> {
+        let mut status = Status::SUCCESS;
+        let info = crate::try_completion!(status, self.bar_info(bar));
+        if info.io || info.base == 0 {
+            return Err(Status::UNSUPPORTED.into());
+        }
+        match usize::try_from(info.base) {
+            Ok(base) => status.into_with_val(|| base as *mut u8),
+            Err(_) => Err(Status::UNSUPPORTED.into())
+        }
+    }
+
+    /// Iterate over the regions decoded by the controller.
+    ///
+    /// Bridges only have two Base Address Registers, and controllers with
+    /// an unknown header layout none at all. Registers holding zero are
+    /// taken as unimplemented and skipped, as is the upper half of 64 bit
+    /// memory regions. Iteration stops early if a register cannot be read,
+    /// and warnings encountered along the way are logged.
+    pub fn regions(&self) -> Result<Regions<'_>> {
+        let mut status = Status::SUCCESS;
+        let header_type = crate::try_completion!(status, self.read_config_single::<u8>(ConfigRegister::HEADER_TYPE.0));
+        let count = match header_type & HEADER_TYPE_LAYOUT {
+            0 => 6,
+            1 => 2,
+            _ => 0
+        };
+        status.into_with_val(|| Regions {
+            pci: self,
+            next: 0,
+            count
+        })
+    }
+}
+
+/// Decoded Base Address Register, see `PciIO::bar_info`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct BarInfo {
+    /// Index of the register
+    pub bar: IoRegister,
+    /// Base address of the region, without the type flags
+    pub base: u64,
+    /// Whether the region is in I/O space rather than memory space
+    pub io: bool,
+    /// Whether reads of the memory region have no side effects, so that it
+    /// may be prefetched or mapped with write combining
+    pub prefetchable: bool,
+    /// Whether the memory region is decoded with 64 bit addresses, using
+    /// the following register as the upper half
+    pub is_64bit: bool,
+}
+
+/// Iterator over the regions of a controller, see `PciIO::regions`.
+pub struct Regions<'a> {
+    pci: &'a PciIO,
+    next: u8,
+    count: u8,
+}
+
+impl Iterator for Regions<'_> {
+    type Item = BarInfo;
+
+    fn next(&mut self) -> Option<BarInfo> {
+        while self.next < self.count {
+            let bar = IoRegister(self.next);
+            let info = self.pci.bar_info(bar).ok()?.log();
+            self.next += if info.is_64bit { 2 } else { 1 };
+            if info.base != 0 || info.io || info.is_64bit || info.prefetchable {
+                return Some(info);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,33 @@
+//! Bus-agnostic access to memory-mapped registers.
+
+use super::{IoRegister, PciIO, ToIoWidth};
+use crate::Result;
+
+/// Memory-mapped register access, independent of the underlying bus.
+///
+/// Drivers for devices which can sit on different buses can be written
+/// against this trait, and tested against any implementation of it. The
+/// registers of a device are grouped in regions, which are the Base
+/// Address Registers for `PciIO`.
+pub trait MmioBus {
+    /// Identifier of a register region of the device
+    type Region: Copy;
+
+    /// Read a single register at an offset into a region
+    fn read<T: ToIoWidth>(&self, region: Self::Region, offset: u64) -> Result<T>;
+
+    /// Write a single register at an offset into a region
+    fn write<T: ToIoWidth>(&self, region: Self::Region, offset: u64, value: T) -> Result;
+}
+
+impl MmioBus for PciIO {
+    type Region = IoRegister;
+
+    fn read<T: ToIoWidth>(&self, region: IoRegister, offset: u64) -> Result<T> {
+        self.read_mem_single(region, offset)
+    }
+
+    fn write<T: ToIoWidth>(&self, region: IoRegister, offset: u64, value: T) -> Result {
+        self.write_mem(region, offset, core::slice::from_ref(&value))
+    }
+}
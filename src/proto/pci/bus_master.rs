@@ -0,0 +1,43 @@
+//! Scoped bus master enable.
+
+use super::{AttributeOperation, PciAttributes, PciIO};
+use crate::{Result, ResultExt};
+
+impl PciIO {
+    /// Allow the controller to act as a bus master until the returned guard
+    /// is dropped.
+    ///
+    /// Bus mastering is enabled through the attributes of the controller,
+    /// as the specification requires, rather than by writing the command
+    /// register. Disabling it when the DMA operations are over keeps the
+    /// device from writing to memory after the driver is done with it.
+    pub fn enable_bus_master_scoped(&self) -> Result<BusMasterGuard<'_>> {
+        self.attributes(AttributeOperation::Enable, PciAttributes::BUS_MASTER)
+            .map_inner(|_| BusMasterGuard { pci: self })
+    }
+}
+
+/// Guard keeping bus mastering enabled, see
+/// `PciIO::enable_bus_master_scoped`.
+///
+/// Mappings should be dropped before the guard, so that the device cannot
+/// access memory through a mapping it is no longer allowed to use.
+pub struct BusMasterGuard<'a> {
+    pci: &'a PciIO,
+}
+
+impl<'a> BusMasterGuard<'a> {
+    /// Protocol bus mastering was enabled on.
+    pub fn pci(&self) -> &'a PciIO {
+        self.pci
+    }
+}
+
+impl<'a> Drop for BusMasterGuard<'a> {
+    fn drop(&mut self) {
+        self.pci
+            .attributes(AttributeOperation::Disable, PciAttributes::BUS_MASTER)
+            .expect("failed to disable bus mastering")
+            .log();
+    }
+}
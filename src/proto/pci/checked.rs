@@ -0,0 +1,84 @@
+//! Bounds-checked memory accesses.
+
+use super::{IoRegister, PciIO, ToIoWidth};
+use crate::{Result, Status};
+use core::mem;
+
+/// Number of Base Address Registers of a type 0 header
+const BAR_COUNT: usize = 6;
+
+impl PciIO {
+    /// Size the memory regions of the controller, to access them with
+    /// bounds checks.
+    ///
+    /// Sizing is done once, with `bar_size`, so the controller must not be
+    /// in use when this is called. Warnings encountered while iterating
+    /// over the regions are logged, as in `regions`.
+    pub fn checked_mmio(&self) -> Result<CheckedMmio<'_>> {
+        let mut status = Status::SUCCESS;
+        let mut sizes = [0u64; BAR_COUNT];
+        for info in crate::try_completion!(status, self.regions()) {
+            if !info.io {
+                sizes[usize::from(info.bar.0)] = crate::try_completion!(status, self.bar_size(info.bar));
+            }
+        }
+        status.into_with_val(|| CheckedMmio { pci: self, sizes })
+    }
+}
+
+/// Memory accesses checked against the size of the regions, see
+/// `PciIO::checked_mmio`.
+///
+/// Accesses which do not fit in their region fail with `INVALID_PARAMETER`
+/// instead of reaching the registers next to it. I/O space regions and
+/// unimplemented registers have a size of zero, so every access to them
+/// fails.
+#[derive(Clone, Copy)]
+pub struct CheckedMmio<'a> {
+    pci: &'a PciIO,
+    sizes: [u64; BAR_COUNT],
+}
+
+impl<'a> CheckedMmio<'a> {
+    /// Protocol the accesses are made through.
+    pub fn pci(&self) -> &'a PciIO {
+        self.pci
+    }
+
+    /// Size of a memory region, as found when the regions were sized.
+    pub fn size(&self, bar: IoRegister) -> u64 {
+        self.sizes.get(usize::from(bar.0)).copied().unwrap_or(0)
+    }
+
+    /// Read a memory region into a storage provided by a slice.
+    pub fn read_mem<T: ToIoWidth>(&self, bar: IoRegister, offset: u64, buffer: &mut [T]) -> Result {
+        if !self.fits::<T>(bar, offset, buffer.len()) {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        self.pci.read_mem(bar, offset, buffer)
+    }
+
+    /// Read a memory region into an object storage.
+    pub fn read_mem_single<T: ToIoWidth>(&self, bar: IoRegister, offset: u64) -> Result<T> {
+        if !self.fits::<T>(bar, offset, 1) {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        self.pci.read_mem_single(bar, offset)
+    }
+
+    /// Write a number of objects into a memory region.
+    pub fn write_mem<T: ToIoWidth>(&self, bar: IoRegister, offset: u64, buffer: &[T]) -> Result {
+        if !self.fits::<T>(bar, offset, buffer.len()) {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        self.pci.write_mem(bar, offset, buffer)
+    }
+
+    /// Check whether `count` objects at `offset` lie within a region.
+    fn fits<T>(&self, bar: IoRegister, offset: u64, count: usize) -> bool {
+        let end = mem::size_of::<T>()
+            .checked_mul(count)
+            .and_then(|len| offset.checked_add(len as u64));
+        matches!(end, Some(end) if end <= self.size(bar))
+    }
+}
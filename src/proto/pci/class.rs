@@ -0,0 +1,76 @@
+//! Device classification.
+
+use super::{ConfigRegister, PciIO};
+use crate::{Result, ResultExt};
+#[cfg(feature = "exts")]
+use crate::table::boot::BootServices;
+#[cfg(feature = "exts")]
+use crate::Handle;
+
+impl PciIO {
+    /// Read the class code of the controller.
+    pub fn class(&self) -> Result<ClassCode> {
+        self.read_config_array::<3>(ConfigRegister::CLASS_CODE.0)
+            .map_inner(|class_code| ClassCode {
+                interface: class_code[0],
+                subclass: class_code[1],
+                class: PciClass(class_code[2])
+            })
+    }
+
+    /// Iterate over the PCI controllers of the given base class.
+    ///
+    /// Controllers whose class code cannot be read are skipped, and the
+    /// warnings encountered while reading them are logged.
+    #[cfg(feature = "exts")]
+    pub fn find_by_class(bt: &BootServices, class: PciClass) -> Result<impl Iterator<Item = (Handle, &PciIO)> + '_> {
+        PciIO::all(bt)
+            .map_inner(move |controllers| {
+                controllers.filter(move |(_, pci)| {
+                    pci.class()
+                        .map(|completion| completion.log().class == class)
+                        .unwrap_or(false)
+                })
+            })
+    }
+}
+
+/// Class code of a controller, identifying its generic function.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ClassCode {
+    /// Base class
+    pub class: PciClass,
+    /// Sub-class, whose meaning depends on the base class
+    pub subclass: u8,
+    /// Register-level programming interface, whose meaning depends on
+    /// the sub-class
+    pub interface: u8,
+}
+
+newtype_enum! {
+    /// Base class of a controller.
+    pub enum PciClass: u8 => {
+        UNCLASSIFIED                  = 0x00,
+        MASS_STORAGE                  = 0x01,
+        NETWORK                       = 0x02,
+        DISPLAY                       = 0x03,
+        MULTIMEDIA                    = 0x04,
+        MEMORY                        = 0x05,
+        BRIDGE                        = 0x06,
+        SIMPLE_COMMUNICATION          = 0x07,
+        BASE_SYSTEM_PERIPHERAL        = 0x08,
+        INPUT                         = 0x09,
+        DOCKING_STATION               = 0x0a,
+        PROCESSOR                     = 0x0b,
+        SERIAL_BUS                    = 0x0c,
+        WIRELESS                      = 0x0d,
+        INTELLIGENT_IO                = 0x0e,
+        SATELLITE_COMMUNICATION       = 0x0f,
+        ENCRYPTION                    = 0x10,
+        SIGNAL_PROCESSING             = 0x11,
+        PROCESSING_ACCELERATOR        = 0x12,
+        NON_ESSENTIAL_INSTRUMENTATION = 0x13,
+        /// Device does not fit in any defined class
+        UNASSIGNED                    = 0xff,
+    }
+}
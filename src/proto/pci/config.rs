@@ -0,0 +1,293 @@
+//! Configuration space access.
+
+#[cfg(feature = "bytemuck")]
+use super::IoWidth;
+use super::{PciIO, ToIoWidth};
+use crate::table::boot::BootServices;
+use crate::{Result, ResultExt, Status};
+use core::ops::BitAnd;
+
+impl PciIO {
+    /// Access PCI configuration space through a dedicated view.
+    pub fn config(&self) -> PciConfigSpace<'_> {
+        PciConfigSpace { pci: self }
+    }
+
+    /// Read a fixed amount of bytes from configuration space.
+    ///
+    /// This is `read_config` into an array which is returned by value, for
+    /// regions whose size is known at compile time.
+    pub fn read_config_array<const N: usize>(&self, offset: u32) -> Result<[u8; N]> {
+        let mut bytes = [0u8; N];
+        self.read_config(offset, &mut bytes)
+            .map_inner(|_| bytes)
+    }
+
+    /// Read the Cache Line Size register, in units of 32 bit words.
+    pub fn cache_line_size(&self) -> Result<u8> {
+        self.config().read_reg(ConfigRegister::CACHE_LINE_SIZE)
+    }
+
+    /// Write the Cache Line Size register, in units of 32 bit words.
+    ///
+    /// Controllers which do not support the given size may ignore the
+    /// write, so the register reads back as zero.
+    pub fn set_cache_line_size(&self, size: u8) -> Result {
+        self.config().write_reg(ConfigRegister::CACHE_LINE_SIZE, size)
+    }
+
+    /// Read the Latency Timer register, in units of PCI bus clocks.
+    pub fn latency_timer(&self) -> Result<u8> {
+        self.config().read_reg(ConfigRegister::LATENCY_TIMER)
+    }
+
+    /// Write the Latency Timer register, in units of PCI bus clocks.
+    ///
+    /// The register is read-only on PCI Express controllers, which have no
+    /// use for it.
+    pub fn set_latency_timer(&self, timer: u8) -> Result {
+        self.config().write_reg(ConfigRegister::LATENCY_TIMER, timer)
+    }
+
+    /// Wait until the masked value of a configuration space register
+    /// matches the expected one, returning the last value read.
+    ///
+    /// The firmware only polls memory and I/O space, so this reads the
+    /// register in a loop, using the boot services to wait between reads.
+    /// The timeout is given in units of 100 ns, like for the firmware poll
+    /// functions. Fails with `TIMEOUT` if the value does not match in time.
+    pub fn poll_config<T>(&self, bt: &BootServices, offset: u32, mask: T, value: T, timeout_100ns: u64) -> Result<T>
+    where T: ToIoWidth + BitAnd<Output = T> + Copy + PartialEq, {
+        let mut status = Status::SUCCESS;
+        let mut elapsed = 0;
+        loop {
+            let current = crate::try_completion!(status, self.read_config_single::<T>(offset));
+            if current & mask == value {
+                return status.into_with_val(|| current);
+            }
+            if elapsed >= timeout_100ns {
+                return Err(Status::TIMEOUT.into());
+            }
+            bt.stall(POLL_INTERVAL_US);
+            elapsed += POLL_INTERVAL_US as u64 * 10;
+        }
+    }
+
+    /// Read the whole standard configuration space, one dword at a time.
+    ///
+    /// The iterator yields the offset and value of each of the 64 dwords,
+    /// which is meant for hexdumps and for comparing the state of the
+    /// controller before and after a driver runs. The space is read in full
+    /// before this returns, so this is for inspection only: some devices
+    /// have registers whose read clears status bits or pops a FIFO, and
+    /// reading them through this function triggers these side effects.
+    pub fn config_dwords(&self) -> Result<impl Iterator<Item = (u32, u32)>> {
+        let mut dwords = [0u32; CONFIG_SPACE_SIZE / 4];
+        self.read_config(0, &mut dwords)
+            .map_inner(move |_| {
+                (0..dwords.len()).map(move |i| (4 * i as u32, dwords[i]))
+            })
+    }
+
+    /// Read a plain data structure from configuration space.
+    ///
+    /// The structure is read with the widest access that both its size and
+    /// the offset are a multiple of. Unlike `read_config_single`, any
+    /// structure without padding can be read, which is useful for vendor
+    /// specific capabilities.
+    #[cfg(feature = "bytemuck")]
+    pub fn read_config_struct<T: bytemuck::Pod>(&self, offset: u32) -> Result<T> {
+        let mut value = T::zeroed();
+        let bytes = bytemuck::bytes_of_mut(&mut value);
+        let size = bytes.len();
+        let (width, count) = if size % 4 == 0 && offset % 4 == 0 {
+            (IoWidth::U32, size / 4)
+        } else if size % 2 == 0 && offset % 2 == 0 {
+            (IoWidth::U16, size / 2)
+        } else {
+            (IoWidth::U8, size)
+        };
+        crate::efi_call!((self.config.read)(self, width, offset, count, bytes.as_mut_ptr()) => value)
+    }
+}
+
+/// View of the PCI configuration space of a controller.
+///
+/// This is a lightweight borrow of the `PciIO` protocol which only
+/// exposes the configuration space accessors.
+#[derive(Clone, Copy)]
+pub struct PciConfigSpace<'a> {
+    pci: &'a PciIO,
+}
+
+impl<'a> PciConfigSpace<'a> {
+    /// Protocol this view was created from.
+    pub fn pci(&self) -> &'a PciIO {
+        self.pci
+    }
+
+    /// Read configuration space into a storage provided by a slice
+    pub fn read<T: ToIoWidth>(&self, offset: u32, buffer: &mut [T]) -> Result {
+        self.pci.read_config(offset, buffer)
+    }
+
+    /// Read a single object from configuration space
+    pub fn read_single<T: ToIoWidth>(&self, offset: u32) -> Result<T> {
+        self.pci.read_config_single(offset)
+    }
+
+    /// Read a plain data structure from configuration space
+    #[cfg(feature = "bytemuck")]
+    pub fn read_struct<T: bytemuck::Pod>(&self, offset: u32) -> Result<T> {
+        self.pci.read_config_struct(offset)
+    }
+
+    /// Write a number of objects into configuration space
+    pub fn write<T: ToIoWidth>(&self, offset: u32, buffer: &[T]) -> Result {
+        self.pci.write_config(offset, buffer)
+    }
+
+    /// Read a register of the standard configuration header
+    pub fn read_reg<T: ToIoWidth>(&self, reg: ConfigRegister) -> Result<T> {
+        self.read_single(reg.0)
+    }
+
+    /// Write a register of the standard configuration header
+    pub fn write_reg<T: ToIoWidth>(&self, reg: ConfigRegister, value: T) -> Result {
+        self.write(reg.0, core::slice::from_ref(&value))
+    }
+
+    /// Iterate over the capability list of the controller.
+    ///
+    /// The list is empty if the controller does not advertise one in its
+    /// status register. Iteration stops early if reading a capability
+    /// header fails, and warnings encountered along the way are logged.
+    pub fn capabilities(&self) -> Result<Capabilities<'a>> {
+        let mut status = Status::SUCCESS;
+        let device_status = crate::try_completion!(status, self.read_reg::<u16>(ConfigRegister::STATUS));
+        let next = if device_status & STATUS_CAPABILITIES_LIST != 0 {
+            crate::try_completion!(status, self.read_reg::<u8>(ConfigRegister::CAPABILITIES_PTR))
+        } else {
+            0
+        };
+        status.into_with_val(|| Capabilities {
+            config: *self,
+            next,
+            remaining: MAX_CAPABILITIES
+        })
+    }
+
+    /// Look up the first capability with the specified identifier.
+    pub fn find_capability(&self, id: CapabilityId) -> Result<Option<Capability>> {
+        self.capabilities()
+            .map_inner(|mut caps| caps.find(|cap| cap.id == id))
+    }
+}
+
+/// Size of the standard configuration space, excluding the PCI Express
+/// extended configuration space
+const CONFIG_SPACE_SIZE: usize = 256;
+
+/// Delay between two reads of `poll_config`, in microseconds
+const POLL_INTERVAL_US: usize = 10;
+
+/// Bit of the status register advertising a capability list
+const STATUS_CAPABILITIES_LIST: u16 = 1 << 4;
+
+/// Upper bound on the length of the capability list, which protects against
+/// looping forever on a malformed list. Capabilities are dword-aligned and
+/// live after the standard header, so there can be at most 48 of them.
+const MAX_CAPABILITIES: usize = (256 - 0x40) / 4;
+
+/// Capability structure found in configuration space.
+#[derive(Clone, Copy, Debug)]
+pub struct Capability {
+    /// Identifier of the capability
+    pub id: CapabilityId,
+    /// Offset of the capability structure in configuration space
+    pub offset: u32,
+}
+
+/// Iterator over the capability list, see `PciConfigSpace::capabilities`.
+pub struct Capabilities<'a> {
+    config: PciConfigSpace<'a>,
+    next: u8,
+    remaining: usize,
+}
+
+impl Iterator for Capabilities<'_> {
+    type Item = Capability;
+
+    fn next(&mut self) -> Option<Capability> {
+        // Pointers into the standard header terminate the list
+        if self.next < 0x40 || self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let offset = u32::from(self.next & !0x3);
+        // The low byte is the capability ID, the high byte points to the next one
+        let header = self.config.read_single::<u16>(offset).ok()?.log();
+        self.next = (header >> 8) as u8;
+        Some(Capability {
+            id: CapabilityId(header as u8),
+            offset
+        })
+    }
+}
+
+newtype_enum! {
+    /// Identifier of a capability structure.
+    pub enum CapabilityId: u8 => {
+        POWER_MANAGEMENT        = 0x01,
+        AGP                     = 0x02,
+        VITAL_PRODUCT_DATA      = 0x03,
+        SLOT_ID                 = 0x04,
+        MSI                     = 0x05,
+        HOT_SWAP                = 0x06,
+        PCI_X                   = 0x07,
+        HYPER_TRANSPORT         = 0x08,
+        VENDOR_SPECIFIC         = 0x09,
+        DEBUG_PORT              = 0x0a,
+        COMPACT_PCI             = 0x0b,
+        HOT_PLUG                = 0x0c,
+        BRIDGE_SUBSYSTEM_VENDOR = 0x0d,
+        AGP_8X                  = 0x0e,
+        SECURE_DEVICE           = 0x0f,
+        PCI_EXPRESS             = 0x10,
+        MSI_X                   = 0x11,
+        SATA                    = 0x12,
+        ADVANCED_FEATURES       = 0x13,
+        ENHANCED_ALLOCATION     = 0x14,
+    }
+}
+
+newtype_enum! {
+    /// Offset of a register in the standard PCI configuration header.
+    pub enum ConfigRegister: u32 => {
+        VENDOR_ID           = 0x00,
+        DEVICE_ID           = 0x02,
+        COMMAND             = 0x04,
+        STATUS              = 0x06,
+        REVISION_ID         = 0x08,
+        CLASS_CODE          = 0x09,
+        CACHE_LINE_SIZE     = 0x0c,
+        LATENCY_TIMER       = 0x0d,
+        HEADER_TYPE         = 0x0e,
+        BIST                = 0x0f,
+        BAR0                = 0x10,
+        BAR1                = 0x14,
+        BAR2                = 0x18,
+        BAR3                = 0x1c,
+        BAR4                = 0x20,
+        BAR5                = 0x24,
+        CARDBUS_CIS         = 0x28,
+        SUBSYSTEM_VENDOR_ID = 0x2c,
+        SUBSYSTEM_ID        = 0x2e,
+        EXPANSION_ROM       = 0x30,
+        CAPABILITIES_PTR    = 0x34,
+        INTERRUPT_LINE      = 0x3c,
+        INTERRUPT_PIN       = 0x3d,
+        MIN_GRANT           = 0x3e,
+        MAX_LATENCY         = 0x3f,
+    }
+}
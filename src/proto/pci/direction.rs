@@ -0,0 +1,52 @@
+//! Type-level direction of DMA mappings.
+//!
+//! The marker types of this module select the `IoOperation` a `MappingEx`
+//! is created for, and which of its accessors are available.
+
+use super::IoOperation;
+
+/// Direction of a DMA mapping, fixed when the mapping is created.
+pub trait DmaDirection {
+    /// Operation the mapping is created for
+    const OPERATION: IoOperation;
+}
+
+/// Directions in which the device writes to system memory, so that the
+/// processor can read its output.
+pub trait DeviceToHost: DmaDirection {}
+
+/// Directions in which the device reads system memory, so that the
+/// processor can provide its input.
+pub trait HostToDevice: DmaDirection {}
+
+/// The device reads the object.
+#[derive(Debug)]
+pub enum BusMasterRead {}
+
+/// The device writes the object.
+#[derive(Debug)]
+pub enum BusMasterWrite {}
+
+/// Both the device and the processor read and write the object.
+#[derive(Debug)]
+pub enum BusMasterCommonBuffer {}
+
+impl DmaDirection for BusMasterRead {
+    const OPERATION: IoOperation = IoOperation::BusMasterRead;
+}
+
+impl DmaDirection for BusMasterWrite {
+    const OPERATION: IoOperation = IoOperation::BusMasterWrite;
+}
+
+impl DmaDirection for BusMasterCommonBuffer {
+    const OPERATION: IoOperation = IoOperation::BusMasterCommonBuffer;
+}
+
+impl HostToDevice for BusMasterRead {}
+
+impl DeviceToHost for BusMasterWrite {}
+
+impl HostToDevice for BusMasterCommonBuffer {}
+
+impl DeviceToHost for BusMasterCommonBuffer {}
@@ -0,0 +1,40 @@
+//! Legacy INTx interrupt control.
+
+use super::{ConfigRegister, PciIO};
+use crate::{Result, ResultExt, Status};
+
+/// Command register bit keeping the controller from asserting INTx
+const COMMAND_INTERRUPT_DISABLE: u16 = 1 << 10;
+/// Status register bit set while an INTx interrupt is pending
+const STATUS_INTERRUPT: u16 = 1 << 3;
+
+impl PciIO {
+    /// Keep the controller from asserting its legacy INTx interrupt, for
+    /// instance when switching to MSI.
+    pub fn mask_intx(&self) -> Result {
+        self.update_command(|command| command | COMMAND_INTERRUPT_DISABLE)
+    }
+
+    /// Allow the controller to assert its legacy INTx interrupt again.
+    pub fn unmask_intx(&self) -> Result {
+        self.update_command(|command| command & !COMMAND_INTERRUPT_DISABLE)
+    }
+
+    /// Check whether the controller has an INTx interrupt pending.
+    ///
+    /// The status is reported whether or not INTx is masked.
+    pub fn intx_status(&self) -> Result<bool> {
+        self.config()
+            .read_reg::<u16>(ConfigRegister::STATUS)
+            .map_inner(|status| status & STATUS_INTERRUPT != 0)
+    }
+
+    /// Read, modify and write back the command register.
+    fn update_command(&self, f: impl FnOnce(u16) -> u16) -> Result {
+        let config = self.config();
+        let mut status = Status::SUCCESS;
+        let command = crate::try_completion!(status, config.read_reg::<u16>(ConfigRegister::COMMAND));
+        crate::try_completion!(status, config.write_reg(ConfigRegister::COMMAND, f(command)));
+        status.into()
+    }
+}
@@ -0,0 +1,51 @@
+//! Controller location on the PCI bus.
+
+use super::PciIO;
+use crate::Result;
+#[cfg(feature = "exts")]
+use crate::table::boot::BootServices;
+#[cfg(feature = "exts")]
+use crate::Status;
+
+impl PciIO {
+    /// Retrieve the segment, bus, device and function numbers of the
+    /// controller.
+    pub fn location(&self) -> Result<PciLocation> {
+        let mut location = PciLocation::default();
+        crate::efi_call!((self.get_location)(self, &mut location.segment, &mut location.bus, &mut location.device, &mut location.function) => location)
+    }
+
+    /// Iterate over the other functions of the same multi-function device.
+    ///
+    /// These are the controllers with the same segment, bus and device
+    /// numbers as this one, but a different function number. Controllers
+    /// whose location cannot be retrieved are skipped, and the warnings
+    /// encountered are logged. The interfaces are obtained like in `all`.
+    #[cfg(feature = "exts")]
+    pub fn sibling_functions<'a>(&self, bt: &'a BootServices) -> Result<impl Iterator<Item = &'a PciIO> + 'a> {
+        let mut status = Status::SUCCESS;
+        let this = crate::try_completion!(status, self.location());
+        let controllers = crate::try_completion!(status, PciIO::all(bt));
+        status.into_with_val(move || {
+            controllers.filter_map(move |(_, pci)| {
+                let other = pci.location().ok()?.log();
+                let sibling = (other.segment, other.bus, other.device) == (this.segment, this.bus, this.device)
+                    && other.function != this.function;
+                if sibling { Some(pci) } else { None }
+            })
+        })
+    }
+}
+
+/// Location of a controller, see `PciIO::location`.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PciLocation {
+    /// PCI segment number
+    pub segment: usize,
+    /// PCI bus number
+    pub bus: usize,
+    /// PCI device number
+    pub device: usize,
+    /// PCI function number
+    pub function: usize,
+}
@@ -0,0 +1,109 @@
+//! Declarative device matching.
+
+use super::{ConfigRegister, PciClass, PciIO};
+use crate::{Result, ResultExt, Status};
+
+impl PciIO {
+    /// Read the subsystem vendor and subsystem IDs of the controller, which
+    /// tell apart the boards built around the same device.
+    ///
+    /// These registers are only defined for type 0 headers.
+    pub fn subsystem_ids(&self) -> Result<(u16, u16)> {
+        self.read_config_single::<u32>(ConfigRegister::SUBSYSTEM_VENDOR_ID.0)
+            .map_inner(|ids| (ids as u16, (ids >> 16) as u16))
+    }
+
+    /// Check whether the controller matches a specification.
+    ///
+    /// Only the registers needed by the specification are read.
+    pub fn matches(&self, spec: &MatchSpec) -> Result<bool> {
+        let mut status = Status::SUCCESS;
+        if spec.vendor.is_some() || spec.device.is_some() {
+            let ids = crate::try_completion!(status, self.read_config_single::<u32>(ConfigRegister::VENDOR_ID.0));
+            if !spec.matches_ids((ids as u16, (ids >> 16) as u16)) {
+                return status.into_with_val(|| false);
+            }
+        }
+        if spec.subsystem_vendor.is_some() || spec.subsystem.is_some() {
+            let ids = crate::try_completion!(status, self.subsystem_ids());
+            if !spec.matches_subsystem_ids(ids) {
+                return status.into_with_val(|| false);
+            }
+        }
+        if let Some(class) = spec.class {
+            let class_code = crate::try_completion!(status, self.class());
+            if class_code.class != class {
+                return status.into_with_val(|| false);
+            }
+        }
+        status.into_with_val(|| true)
+    }
+}
+
+/// Specification of the controllers a driver supports, see
+/// `PciIO::matches`.
+///
+/// Every field which is left unspecified matches any value, so the default
+/// specification matches every controller.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MatchSpec {
+    vendor: Option<u16>,
+    device: Option<u16>,
+    subsystem_vendor: Option<u16>,
+    subsystem: Option<u16>,
+    class: Option<PciClass>,
+}
+
+impl MatchSpec {
+    /// Start a specification matching every controller.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only match controllers with the given vendor ID.
+    pub fn vendor(mut self, vendor: u16) -> Self {
+        self.vendor = Some(vendor);
+        self
+    }
+
+    /// Only match controllers with the given device ID.
+    pub fn device(mut self, device: u16) -> Self {
+        self.device = Some(device);
+        self
+    }
+
+    /// Only match controllers with the given subsystem vendor ID.
+    pub fn subsystem_vendor(mut self, subsystem_vendor: u16) -> Self {
+        self.subsystem_vendor = Some(subsystem_vendor);
+        self
+    }
+
+    /// Only match controllers with the given subsystem ID.
+    pub fn subsystem(mut self, subsystem: u16) -> Self {
+        self.subsystem = Some(subsystem);
+        self
+    }
+
+    /// Only match controllers of the given base class.
+    pub fn class(mut self, class: PciClass) -> Self {
+        self.class = Some(class);
+        self
+    }
+
+    fn matches_ids(&self, (vendor, device): (u16, u16)) -> bool {
+        field_matches(self.vendor, vendor) && field_matches(self.device, device)
+    }
+
+    fn matches_subsystem_ids(&self, (vendor, subsystem): (u16, u16)) -> bool {
+        field_matches(self.subsystem_vendor, vendor) && field_matches(self.subsystem, subsystem)
+    }
+}
+
+/// Check an ID against a field of a specification, unspecified fields
+/// matching any ID
+fn field_matches(expected: Option<u16>, id: u16) -> bool {
+    match expected {
+        Some(expected) => expected == id,
+        None => true
+    }
+}
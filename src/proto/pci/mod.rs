@@ -0,0 +1,1212 @@
+use crate::proto::Protocol;
+use crate::table::boot::MemoryType;
+use crate::{unsafe_guid, Status, Result};
+#[cfg(feature = "exts")]
+use crate::table::boot::BootServices;
+use crate::ResultExt;
+#[cfg(feature = "exts")]
+use crate::Handle;
+use bitflags::bitflags;
+use core::ffi::c_void;
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use core::ptr::NonNull;
+
+#[cfg(feature = "exts")]
+use alloc_api::boxed::Box;
+
+mod bar;
+pub use self::bar::{BarInfo, Regions};
+
+mod bus;
+pub use self::bus::MmioBus;
+
+mod bus_master;
+pub use self::bus_master::BusMasterGuard;
+
+mod checked;
+pub use self::checked::CheckedMmio;
+
+mod class;
+pub use self::class::{ClassCode, PciClass};
+
+mod config;
+pub use self::config::{Capabilities, Capability, CapabilityId, ConfigRegister, PciConfigSpace};
+
+pub mod direction;
+use self::direction::{DeviceToHost, DmaDirection, HostToDevice};
+
+mod intx;
+
+mod location;
+pub use self::location::PciLocation;
+
+mod matching;
+pub use self::matching::MatchSpec;
+
+mod power;
+pub use self::power::PowerState;
+
+mod reset;
+
+mod rom;
+pub use self::rom::{EfiRomImage, OptionRom, RomCodeType, RomImage, RomImages};
+
+mod snapshot;
+pub use self::snapshot::ConfigSnapshot;
+
+mod vendor;
+pub use self::vendor::VendorCap;
+
+#[cfg(feature = "exts")]
+mod tracking;
+
+#[cfg(all(feature = "test-utils", feature = "exts"))]
+mod recording;
+#[cfg(all(feature = "test-utils", feature = "exts"))]
+pub use self::recording::{Access, AccessKind, AccessSpace, RecordingPciIO};
+
+type IoReadFn = extern "efiapi" fn(this: &PciIO, width: IoWidth, bar: IoRegister, offset: u64, count: usize, buffer: *mut u8) -> Status;
+
+type IoWriteFn = extern "efiapi" fn(this: &PciIO, width: IoWidth, bar: IoRegister, offset: u64, count: usize, buffer: *const u8) -> Status;
+
+type ConfigReadFn = extern "efiapi" fn(this: &PciIO, width: IoWidth, offset: u32, count: usize, buffer: *mut u8) -> Status;
+
+type ConfigWriteFn = extern "efiapi" fn(this: &PciIO, width: IoWidth, offset: u32, count: usize, buffer: *const u8) -> Status;
+
+type MapFn = extern "efiapi" fn(this: &PciIO, op: IoOperation, host_addr: *const c_void, num_bytes: &mut usize, device_addr: &mut u64, mapping: &mut *const c_void) -> Status;
+
+type UnmapFn = extern "efiapi" fn(this: &PciIO, mapping: *const c_void) -> Status;
+
+type AllocateBufferFn = extern "efiapi" fn(this: &PciIO, ty: u32, mem_ty: MemoryType, pages: usize, host_addr: &mut *mut c_void, attributes: u64) -> Status;
+
+type FreeBufferFn = extern "efiapi" fn(this: &PciIO, pages: usize, host_addr: *mut c_void) -> Status;
+
+type FlushFn = extern "efiapi" fn(this: &PciIO) -> Status;
+
+type GetLocationFn = extern "efiapi" fn(this: &PciIO, segment: &mut usize, bus: &mut usize, device: &mut usize, function: &mut usize) -> Status;
+
+type AttributesFn = extern "efiapi" fn(this: &PciIO, op: AttributeOperation, attributes: u64, result: *mut u64) -> Status;
+
+#[repr(C)]
+struct IoSpace {
+    read: IoReadFn,
+    write: IoWriteFn
+}
+
+#[repr(C)]
+struct ConfigSpace {
+    read: ConfigReadFn,
+    write: ConfigWriteFn
+}
+
+/// The PCI I/O protocol, used by drivers to access a PCI controller.
+///
+/// The accessors return the crate's `Result`, whose success path is a
+/// `Completion`: a warning reported by the firmware is not discarded, it
+/// is kept alongside the value. Callers which care can inspect it with
+/// `Completion::status` or `Completion::split` before taking the value,
+/// while `log_warning` and `unwrap_success` cover the common cases.
+///
+/// The protocol interface is neither `Send` nor `Sync`. Boot services,
+/// including the protocols they manage, may only be used by the processor
+/// that booted the system: the firmware takes no lock around the interface,
+/// and the protocol functions must not be called from application
+/// processors started through the MP services. The raw pointers held by the
+/// interface keep the compiler from sharing it between threads, and
+/// `Mapping` and `MappingEx` inherit that restriction.
+#[repr(C)]
+#[unsafe_guid("4cf5b200-68b8-4ca5-9eec-b23e3f50029a")]
+#[derive(Protocol)]
+pub struct PciIO {
+    poll_mem: usize,
+    poll_io: usize,
+    mem: IoSpace,
+    io: IoSpace,
+    config: ConfigSpace,
+    copy_mem: usize,
+    map: MapFn,
+    unmap: UnmapFn,
+    allocate_buffer: AllocateBufferFn,
+    free_buffer: FreeBufferFn,
+    flush: FlushFn,
+    get_location: GetLocationFn,
+    attributes: AttributesFn,
+    get_bar_attributes: usize,
+    set_bar_attributes: usize,
+    rom_size_bytes: u64,
+    rom_image: *const c_void,
+}
+
+/// Marker trait for mapped buffer.
+///
+/// Mapped objects start out zeroed and may then be written by the device,
+/// so implementors must be valid for any bit pattern.
+pub trait Mappable: Sized {}
+
+// Arrays of mappable objects, such as descriptor rings, are mappable
+impl<T: Mappable, const N: usize> Mappable for [T; N] {}
+
+/// Indicate appropriate I/O access size during memory-mapped I/O operations.
+///
+/// Implementors must be plain integer-like types: their size must be the
+/// byte count of `IO_WIDTH`, they must not contain padding, and any bit
+/// pattern must be a valid value. The single-element readers fill the
+/// value directly from the device and assume it is fully initialized.
+pub trait ToIoWidth {
+    /// Width of a single access to an object of this type
+    const IO_WIDTH: IoWidth;
+}
+
+/// Check that a single access of `T::IO_WIDTH` fills all of `T`
+fn debug_assert_single<T: ToIoWidth>() {
+    debug_assert_eq!(
+        T::IO_WIDTH.byte_size(),
+        Some(core::mem::size_of::<T>()),
+        "Size of the value does not match its I/O width"
+    );
+}
+
+impl ToIoWidth for u8 {
+    const IO_WIDTH: IoWidth = IoWidth::U8;
+}
+
+impl ToIoWidth for u16 {
+    const IO_WIDTH: IoWidth = IoWidth::U16;
+}
+
+impl ToIoWidth for u32 {
+    const IO_WIDTH: IoWidth = IoWidth::U32;
+}
+
+impl ToIoWidth for i8 {
+    const IO_WIDTH: IoWidth = IoWidth::U8;
+}
+
+impl ToIoWidth for i16 {
+    const IO_WIDTH: IoWidth = IoWidth::U16;
+}
+
+impl ToIoWidth for i32 {
+    const IO_WIDTH: IoWidth = IoWidth::U32;
+}
+
+/// Implement `ToIoWidth` for `#[repr(transparent)]` newtypes around a type
+/// which already implements it, using the same width
+///
+/// ```
+/// #[repr(transparent)]
+/// #[derive(Clone, Copy)]
+/// struct Doorbell(u32);
+///
+/// impl_to_io_width!(Doorbell => u32);
+///
+/// let doorbell = pci.read_mem_single::<Doorbell>(IoRegister::R0, 0x100)?;
+/// ```
+#[macro_export]
+macro_rules! impl_to_io_width {
+    ($($ty:ty => $inner:ty),* $(,)?) => {
+        $(
+            impl $crate::proto::pci::ToIoWidth for $ty {
+                const IO_WIDTH: $crate::proto::pci::IoWidth =
+                    <$inner as $crate::proto::pci::ToIoWidth>::IO_WIDTH;
+            }
+        )*
+    };
+}
+
+/// Representation of the bus relative memory address created by Map().
+///
+/// A raw mapping does not borrow the system memory object behind it, see
+/// `MappingEx` and `ScopedMapping` for guards which do, and which unmap it
+/// on drop. It is handed back inside the `Error` of a failed `unmap`.
+///
+/// A mapping translates a range of host memory, as seen by the processor,
+/// into a range of bus relative addresses, as seen by the device. Both
+/// ranges have the same size, so an offset into one is the same offset
+/// into the other.
+///
+/// Two mappings compare equal, and hash alike, when they cover the same bus
+/// relative range, whatever the host memory behind it. This is the range
+/// the device reports in its completions, which makes it a convenient key
+/// for drivers keeping track of many mappings in flight.
+///
+/// A mapping is neither `Send` nor `Sync`, since it holds raw pointers.
+/// This is intended: it must be unmapped through the `PciIO` instance it
+/// was created by, which may not be used from another processor.
+#[derive(Debug)]
+pub struct Mapping {
+    addr: *const c_void,
+    host_addr: *const c_void,
+    device_addr: u64,
+    size: usize
+}
+
+impl Mapping {
+    /// Captured size of the system memory object.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Mapped bus relative address of the system memory object.
+    ///
+    /// This is the address to program into the device, never to be
+    /// dereferenced by the processor.
+    pub fn device_address(&self) -> u64 {
+        self.device_addr
+    }
+
+    /// Address of the system memory object as seen by the processor.
+    ///
+    /// This is the pointer that was passed to `PciIO::map`: when the root
+    /// bridge uses a bounce buffer, the device address does not refer to
+    /// this memory directly and the data is copied on unmap.
+    pub fn host_address(&self) -> *const c_void {
+        self.host_addr
+    }
+
+    /// Translate a bus relative address within this mapping back to the
+    /// host address it was mapped from.
+    pub fn to_host(&self, device_addr: u64) -> Option<*const c_void> {
+        let offset = device_addr.checked_sub(self.device_addr)?;
+        if offset >= self.size as u64 {
+            return None;
+        }
+        Some(unsafe { (self.host_addr as *const u8).add(offset as usize) as *const c_void })
+    }
+
+    /// Translate a host address within this mapping to the bus relative
+    /// address the device should use.
+    pub fn to_device(&self, host_addr: *const c_void) -> Option<u64> {
+        let offset = (host_addr as usize).checked_sub(self.host_addr as usize)?;
+        if offset >= self.size {
+            return None;
+        }
+        Some(self.device_addr + offset as u64)
+    }
+
+    /// Decompose the mapping into the token returned by the firmware, the
+    /// host address, the bus relative address and the size, in that order.
+    ///
+    /// This is for storing the mapping where a Rust value cannot be kept,
+    /// such as a C descriptor block. The mapping stays live, and must
+    /// eventually be rebuilt with `from_raw` to be unmapped.
+    pub fn into_raw(self) -> (*const c_void, *const c_void, u64, usize) {
+        (self.addr, self.host_addr, self.device_addr, self.size)
+    }
+
+    /// Rebuild a mapping decomposed by `into_raw`.
+    ///
+    /// # Safety
+    ///
+    /// The parts must have been returned by `into_raw`, in the same order,
+    /// and no other mapping may have been rebuilt from them.
+    pub unsafe fn from_raw(addr: *const c_void, host_addr: *const c_void, device_addr: u64, size: usize) -> Mapping {
+        Mapping {
+            addr,
+            host_addr,
+            device_addr,
+            size
+        }
+    }
+}
+
+// Mappings are identified by the bus relative range they cover, so that
+// drivers can key their bookkeeping on it
+
+impl PartialEq for Mapping {
+    fn eq(&self, other: &Mapping) -> bool {
+        (self.device_addr, self.size) == (other.device_addr, other.size)
+    }
+}
+
+impl Eq for Mapping {}
+
+impl core::hash::Hash for Mapping {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.device_addr.hash(state);
+        self.size.hash(state);
+    }
+}
+
+/// Guard over a mapped system memory object, which is unmapped (and freed,
+/// if the guard owns it) on drop.
+///
+/// Only `PciIO::map_ex` needs the `exts` feature, since it boxes the object.
+/// `PciIO::map_into` and `PciIO::common_buffer_ex` work without an
+/// allocator, using a caller-provided buffer and pages allocated by the
+/// root bridge respectively.
+///
+/// The direction of the mapping is part of its type: the processor can only
+/// read the object through `get` if the device writes to it, and only fill
+/// it through `get_mut` if the device reads from it. Both are allowed for
+/// common buffers.
+///
+/// Like `Mapping`, a guard is neither `Send` nor `Sync`: it borrows the
+/// `PciIO` instance it unmaps itself through on drop.
+pub struct MappingEx<'a, B: ?Sized, D: DmaDirection> {
+    mapping: Option<Mapping>,
+    pci: &'a PciIO,
+    buffer: Buffer<'a, B>,
+    direction: PhantomData<D>
+}
+
+/// System memory object behind a `MappingEx`
+///
+/// Every variant holds a single raw pointer, derived once when the object
+/// is mapped, from which both the host address handed to the firmware and
+/// the pointers returned to the processor are taken. Keeping a `Box` or a
+/// reference instead would assert unique access whenever it is moved or
+/// reborrowed, invalidating the pointers handed out before.
+enum Buffer<'a, B: ?Sized> {
+    /// Heap allocation, for bus master read or write operations, which is
+    /// turned back into a `Box` on drop
+    #[cfg(feature = "exts")]
+    Boxed(NonNull<B>),
+    /// Pages allocated by the root bridge, for common buffer operations
+    Common { ptr: NonNull<B>, pages: usize },
+    /// Memory owned by the caller
+    Borrowed(NonNull<B>, PhantomData<&'a mut B>)
+}
+
+impl<'a, B: ?Sized> Buffer<'a, B> {
+    fn as_ptr(&self) -> *mut B {
+        match *self {
+            #[cfg(feature = "exts")]
+            Buffer::Boxed(ptr) => ptr.as_ptr(),
+            Buffer::Common { ptr, .. } => ptr.as_ptr(),
+            Buffer::Borrowed(ptr, _) => ptr.as_ptr()
+        }
+    }
+}
+
+impl<'a, B, D> MappingEx<'a, B, D>
+where B: ?Sized + 'a, D: DmaDirection, {
+    /// Expose raw mapping object
+    pub fn mapping(&self) -> &Mapping {
+        self.mapping.as_ref().unwrap()
+    }
+
+    /// Mapped bus relative address of the system memory object.
+    pub fn device_address(&self) -> u64 {
+        self.mapping.as_ref().unwrap().device_address()
+    }
+
+    /// Operation the object was mapped for.
+    pub fn operation(&self) -> IoOperation {
+        D::OPERATION
+    }
+}
+
+impl<'a, B, D> MappingEx<'a, B, D>
+where B: ?Sized + 'a, D: HostToDevice, {
+    /// Pointer to the object, for the processor to fill it with data for
+    /// the device.
+    pub fn get_mut(&mut self) -> *mut B {
+        self.buffer.as_ptr()
+    }
+}
+
+impl<'a, B, D> MappingEx<'a, B, D>
+where B: ?Sized + 'a, D: DeviceToHost, {
+    /// Pointer to the object, for the processor to read the data written
+    /// by the device.
+    pub fn get(&self) -> *const B {
+        self.buffer.as_ptr()
+    }
+}
+
+impl<'a, T, D, const N: usize> MappingEx<'a, [T; N], D>
+where T: 'a, D: DeviceToHost, {
+    /// View of the mapped array, for the processor to read the entries
+    /// written by the device.
+    ///
+    /// The device may keep writing to the array while it is borrowed, so
+    /// entries it owns, such as the descriptors of a ring which it did not
+    /// hand back yet, should not be relied upon.
+    pub fn as_slice(&self) -> &[T] {
+        unsafe { &*self.get() }
+    }
+}
+
+impl<'a, T, D, const N: usize> MappingEx<'a, [T; N], D>
+where T: 'a, D: HostToDevice, {
+    /// Mutable view of the mapped array, for the processor to fill the
+    /// entries read by the device.
+    ///
+    /// Entries which are currently owned by the device should not be
+    /// modified.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { &mut *self.get_mut() }
+    }
+}
+
+/// Mapping of a byte slice borrowed from the caller, unmapped on drop.
+///
+/// See `PciIO::map_bytes`.
+pub struct ScopedMapping<'a> {
+    mapping: Option<Mapping>,
+    pci: &'a PciIO,
+    data: PhantomData<&'a [u8]>
+}
+
+impl<'a> ScopedMapping<'a> {
+    /// Expose raw mapping object
+    pub fn mapping(&self) -> &Mapping {
+        self.mapping.as_ref().unwrap()
+    }
+
+    /// Mapped bus relative address of the slice.
+    pub fn device_address(&self) -> u64 {
+        self.mapping().device_address()
+    }
+}
+
+impl<'a> Drop for ScopedMapping<'a> {
+    fn drop(&mut self) {
+        if let Some(mapping) = self.mapping.take() {
+            self.pci
+                .unmap(mapping)
+                .expect("failed to unmap something")
+                .log();
+        }
+    }
+}
+
+impl<'a, B: ?Sized, D: DmaDirection> Drop for MappingEx<'a, B, D> {
+    fn drop(&mut self) {
+        if let Some(mapping) = self.mapping.take() {
+            self.pci
+                .unmap(mapping)
+                .expect("failed to unmap something");
+            // On error, mapping is moved back into this scope
+        }
+        match self.buffer {
+            #[cfg(feature = "exts")]
+            Buffer::Boxed(ptr) => drop(unsafe { Box::from_raw(ptr.as_ptr()) }),
+            Buffer::Common { ptr, pages } => unsafe {
+                self.pci
+                    .free_buffer(pages, ptr.as_ptr().cast())
+                    .expect("failed to free common buffer")
+                    .log();
+            },
+            Buffer::Borrowed(..) => {}
+        }
+    }
+}
+
+impl PciIO {
+    /// Read PCI configuration space into a storage provided by a slice
+    pub fn read_config<T: ToIoWidth>(&self, offset: u32, buffer: &mut [T]) -> Result {
+        crate::efi_call!((self.config.read)(self, T::IO_WIDTH, offset, buffer.len(), buffer.as_mut_ptr().cast()))
+    }
+
+    /// Read PCI configuration space into a storage provided by an object of size T
+    pub fn read_config_single<T: ToIoWidth>(&self, offset: u32) -> Result<T> {
+        debug_assert_single::<T>();
+        let mut buffer: MaybeUninit<T> = MaybeUninit::uninit();
+        crate::efi_call!((self.config.read)(self, T::IO_WIDTH, offset, 1, buffer.as_mut_ptr().cast()) => unsafe { buffer.assume_init() })
+    }
+
+    /// Write a number of objects into PCI configuration space
+    pub fn write_config<T: ToIoWidth>(&self, offset: u32, buffer: &[T]) -> Result {
+        crate::efi_call!((self.config.write)(self, T::IO_WIDTH, offset, buffer.len(), buffer.as_ptr().cast()))
+    }
+
+    /// Write the values yielded by an iterator into consecutive objects of
+    /// PCI configuration space
+    ///
+    /// Each value is written by its own access, so no buffer is needed.
+    /// Writing stops at the first error.
+    pub fn write_config_iter<T: ToIoWidth>(&self, offset: u32, values: impl Iterator<Item = T>) -> Result {
+        let mut status = Status::SUCCESS;
+        let mut offset = offset;
+        for value in values {
+            crate::try_completion!(status, self.write_config(offset, core::slice::from_ref(&value)));
+            offset += core::mem::size_of::<T>() as u32;
+        }
+        status.into()
+    }
+
+    /// Read I/O port space region into a storage provided by a slice
+    pub fn read_io<T: ToIoWidth>(&self, bar: IoRegister, offset: u64, buffer: &mut [T]) -> Result {
+        crate::efi_call!((self.io.read)(self, T::IO_WIDTH, bar, offset, buffer.len(), buffer.as_mut_ptr().cast()))
+    }
+
+    /// Read I/O port space region into an object storage
+    pub fn read_io_single<T: ToIoWidth>(&self, bar: IoRegister, offset: u64) -> Result<T> {
+        debug_assert_single::<T>();
+        let mut buffer: MaybeUninit<T> = MaybeUninit::uninit();
+        crate::efi_call!((self.io.read)(self, T::IO_WIDTH, bar, offset, 1, buffer.as_mut_ptr().cast()) => unsafe { buffer.assume_init() })
+    }
+
+    /// Write a number of objects into I/O port space region
+    pub fn write_io<T: ToIoWidth>(&self, bar: IoRegister, offset: u64, buffer: &[T]) -> Result {
+        crate::efi_call!((self.io.write)(self, T::IO_WIDTH, bar, offset, buffer.len(), buffer.as_ptr().cast()))
+    }
+
+    /// Read memory-mapped I/O region into a storage provided by a slice
+    pub fn read_mem<T: ToIoWidth>(&self, bar: IoRegister, offset: u64, buffer: &mut [T]) -> Result {
+        crate::efi_call!((self.mem.read)(self, T::IO_WIDTH, bar, offset, buffer.len(), buffer.as_mut_ptr().cast()))
+    }
+
+    /// Write an object into memory-mapped I/O region
+    pub fn read_mem_single<T: ToIoWidth>(&self, bar: IoRegister, offset: u64) -> Result<T> {
+        debug_assert_single::<T>();
+        let mut buffer: MaybeUninit<T> = MaybeUninit::uninit();
+        crate::efi_call!((self.mem.read)(self, T::IO_WIDTH, bar, offset, 1, buffer.as_mut_ptr().cast()) => unsafe { buffer.assume_init() })
+    }
+
+    /// Write number of objects into memory-mapped I/O region
+    pub fn write_mem<T: ToIoWidth>(&self, bar: IoRegister, offset: u64, buffer: &[T]) -> Result {
+        crate::efi_call!((self.mem.write)(self, T::IO_WIDTH, bar, offset, buffer.len(), buffer.as_ptr().cast()))
+    }
+
+    /// Write the values yielded by an iterator into consecutive objects of
+    /// memory-mapped I/O region
+    ///
+    /// Each value is written by its own access, so no buffer is needed.
+    /// Writing stops at the first error.
+    pub fn write_mem_iter<T: ToIoWidth>(&self, bar: IoRegister, offset: u64, values: impl Iterator<Item = T>) -> Result {
+        let mut status = Status::SUCCESS;
+        let mut offset = offset;
+        for value in values {
+            crate::try_completion!(status, self.write_mem(bar, offset, core::slice::from_ref(&value)));
+            offset += core::mem::size_of::<T>() as u64;
+        }
+        status.into()
+    }
+
+    /// Ring a 32-bit doorbell register in memory-mapped I/O region
+    ///
+    /// A fence first makes the descriptors written by the processor visible
+    /// before the device gets notified. The value is then written with a
+    /// single `U32` access, followed by `flush`. The write itself is posted
+    /// like any other: a subsequent read from the device is the only way to
+    /// be sure it got there.
+    pub fn write_doorbell(&self, bar: IoRegister, offset: u64, value: u32) -> Result {
+        core::sync::atomic::fence(core::sync::atomic::Ordering::SeqCst);
+        let mut status = Status::SUCCESS;
+        crate::try_completion!(status, self.write_mem(bar, offset, &[value]));
+        crate::try_completion!(status, self.flush());
+        status.into()
+    }
+
+    /// Read a memory-mapped I/O register whose width is only known at runtime
+    ///
+    /// The value is zero-extended to 64 bits. Only the plain `U8`, `U16`,
+    /// `U32` and `U64` widths are accepted, other widths are rejected with
+    /// `INVALID_PARAMETER`.
+    pub fn read_mem_dyn(&self, bar: IoRegister, offset: u64, width: IoWidth) -> Result<u64> {
+        if !width.is_plain() {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        // UEFI platforms are little-endian, so the register lands in the low bytes
+        let mut buffer = [0u8; 8];
+        crate::efi_call!((self.mem.read)(self, width, bar, offset, 1, buffer.as_mut_ptr()) => u64::from_le_bytes(buffer))
+    }
+
+    /// Write a memory-mapped I/O register whose width is only known at runtime
+    ///
+    /// The value is truncated to the requested width. Only the plain `U8`,
+    /// `U16`, `U32` and `U64` widths are accepted, other widths are
+    /// rejected with `INVALID_PARAMETER`.
+    pub fn write_mem_dyn(&self, bar: IoRegister, offset: u64, width: IoWidth, value: u64) -> Result {
+        if !width.is_plain() {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        let buffer = value.to_le_bytes();
+        crate::efi_call!((self.mem.write)(self, width, bar, offset, 1, buffer.as_ptr()))
+    }
+
+    /// Create bus relative memory address for DMA operation.
+    ///
+    /// This functions allows an external device to access
+    /// the buffer supplied for the duration of the DMA
+    /// operation or until unmap() function is called. It is
+    /// caller responsibility to ensure that the buffer
+    /// lives long enough and has proper size to accomodate
+    /// any access operation by the device.
+    ///
+    /// The caller must also make sure that buffer has
+    /// appropriate cache coherency properties and
+    /// synchronize any store operations on the buffer with
+    /// the device to avoid simultaneous mutation.
+    ///
+    /// The caller must also make sure to wash their hands.
+    ///
+    /// With the `exts` feature, outstanding mappings are tracked and a
+    /// request overlapping a live mapping of the same host memory is
+    /// rejected with `INVALID_PARAMETER`, unless both mappings are
+    /// `BusMasterRead` operations. An event notification function which
+    /// preempted another `map` or `unmap` call fails with `NOT_READY`
+    /// instead of touching the list of mappings it is updating.
+    ///
+    /// Only the three operations of `IoOperation` are valid, which the
+    /// type system guarantees: see `IoOperation::from_raw` for operations
+    /// coming from raw integers.
+    pub unsafe fn map(&self, op: IoOperation, host_addr: *const c_void, num_bytes: usize) -> Result<Mapping> {
+        #[cfg(feature = "exts")]
+        return tracking::track(host_addr, num_bytes, op, || self.map_untracked(op, host_addr, num_bytes));
+        #[cfg(not(feature = "exts"))]
+        self.map_untracked(op, host_addr, num_bytes)
+    }
+
+    unsafe fn map_untracked(&self, op: IoOperation, host_addr: *const c_void, num_bytes: usize) -> Result<Mapping> {
+        let mut out_mapping = core::ptr::null();
+        let mut out_num_bytes = num_bytes;
+        let mut out_device_addr = 0;
+        (self.map)(self, op, host_addr, &mut out_num_bytes, &mut out_device_addr, &mut out_mapping)
+            .into_with_err(|_| {})
+            .map(|completion| {
+                // TBD: -- check out_num_bytes that it matches the request
+                // TBD: -- maybe check for alignment/null at least?
+                completion.map(|_| Mapping {
+                    addr: out_mapping,
+                    host_addr,
+                    device_addr: out_device_addr,
+                    size: out_num_bytes
+                })
+            })
+    }
+
+    #[cfg(feature = "exts")]
+    /// Create bus relative memory address from an object.
+    ///
+    /// The operation is given by the direction `D`. Objects mapped for a
+    /// `BusMasterCommonBuffer` operation are allocated like in
+    /// `common_buffer_ex`, the others are boxed. Without the `exts`
+    /// feature, use `map_into` with a buffer owned by the caller instead.
+    pub fn map_ex<'a, T, D>(&'a self) -> Result<MappingEx<'a, T, D>>
+    where T: Mappable + 'a, D: DmaDirection, {
+        let op = D::OPERATION;
+        if op == IoOperation::BusMasterCommonBuffer {
+            return self.map_common_buffer();
+        }
+        let num_bytes = core::mem::size_of::<T>();
+        let buffer = unsafe { NonNull::new_unchecked(Box::into_raw(Box::<T>::new_zeroed().assume_init())) };
+        let host_addr = buffer.as_ptr() as *const c_void;
+        let result = unsafe {
+            self.map(op, host_addr, num_bytes)
+                .map(|completion| {
+                    MappingEx {
+                        mapping: Some(completion.ignore_warning()),
+                        pci: self,
+                        buffer: Buffer::Boxed(buffer),
+                        direction: PhantomData
+                    }.into()
+                })
+        };
+        if result.is_err() {
+            drop(unsafe { Box::from_raw(buffer.as_ptr()) });
+        }
+        result
+    }
+
+    /// Create a bus relative memory address from a buffer owned by the
+    /// caller, which stays borrowed until the guard is dropped.
+    ///
+    /// This is the allocation-free counterpart of `map_ex`. As with `map`,
+    /// the buffer must have suitable cache coherency properties for a
+    /// `BusMasterCommonBuffer` operation, which is best obtained from
+    /// `common_buffer_ex` or `allocate_buffer`.
+    pub fn map_into<'a, D>(&'a self, buffer: &'a mut [u8]) -> Result<MappingEx<'a, [u8], D>>
+    where D: DmaDirection, {
+        let num_bytes = buffer.len();
+        let buffer = NonNull::from(buffer);
+        let host_addr = buffer.as_ptr() as *const c_void;
+        unsafe {
+            self.map(D::OPERATION, host_addr, num_bytes)
+                .map(move |completion| {
+                    completion.map(move |mapping| MappingEx {
+                        mapping: Some(mapping),
+                        pci: self,
+                        buffer: Buffer::Borrowed(buffer, PhantomData),
+                        direction: PhantomData
+                    })
+                })
+        }
+    }
+
+    /// Create a bus relative memory address for a byte slice, which stays
+    /// borrowed until the mapping is dropped.
+    ///
+    /// This is the safe counterpart of `map` for data the driver already
+    /// owns. Since the slice is borrowed immutably, the device may only
+    /// read from it: operations other than `BusMasterRead` are rejected
+    /// with `INVALID_PARAMETER`, and `map_into` should be used instead.
+    pub fn map_bytes<'a>(&'a self, op: IoOperation, data: &'a [u8]) -> Result<ScopedMapping<'a>> {
+        if op != IoOperation::BusMasterRead {
+            return Err(Status::INVALID_PARAMETER.into());
+        }
+        unsafe { self.map(op, data.as_ptr().cast(), data.len()) }
+            .map_inner(|mapping| ScopedMapping {
+                mapping: Some(mapping),
+                pci: self,
+                data: PhantomData
+            })
+    }
+
+    /// Create a common buffer mapping of an object.
+    ///
+    /// A heap allocation is not guaranteed to be coherent between the
+    /// processor and the device, which a common buffer requires. The
+    /// object is therefore placed in zeroed pages obtained from
+    /// `allocate_buffer`, which are released when the guard is dropped.
+    ///
+    /// Objects aligned to more than a page are `UNSUPPORTED`, and a null
+    /// allocation reported as successful fails with `OUT_OF_RESOURCES`.
+    pub fn common_buffer_ex<'a, T>(&'a self) -> Result<MappingEx<'a, T, direction::BusMasterCommonBuffer>>
+    where T: Mappable + 'a, {
+        self.map_common_buffer()
+    }
+
+    /// Create a common buffer mapping of an array, such as a ring of DMA
+    /// descriptors.
+    ///
+    /// This is `common_buffer_ex` for `[T; N]`, whose guard gives access to
+    /// the entries through `as_slice` and `as_mut_slice`.
+    pub fn map_array<'a, T, const N: usize>(&'a self) -> Result<MappingEx<'a, [T; N], direction::BusMasterCommonBuffer>>
+    where T: Mappable + 'a, {
+        self.common_buffer_ex()
+    }
+
+    /// Common buffer mapping, for any direction whose operation is
+    /// `BusMasterCommonBuffer`
+    fn map_common_buffer<'a, T, D>(&'a self) -> Result<MappingEx<'a, T, D>>
+    where T: Mappable + 'a, D: DmaDirection, {
+        let num_bytes = core::mem::size_of::<T>();
+        if core::mem::align_of::<T>() > PAGE_SIZE {
+            // Common buffers are only page-aligned
+            return Err(Status::UNSUPPORTED.into());
+        }
+        let pages = core::cmp::max(1, num_bytes.div_ceil(PAGE_SIZE));
+        let mut status = Status::SUCCESS;
+        let host_addr = crate::try_completion!(status, self.allocate_buffer(MemoryType::BOOT_SERVICES_DATA, pages, PciAttributes::empty()));
+        let ptr = match NonNull::new(host_addr.cast::<T>()) {
+            Some(ptr) => ptr,
+            None => {
+                let _ = unsafe { self.free_buffer(pages, host_addr) };
+                return Err(Status::OUT_OF_RESOURCES.into());
+            }
+        };
+        unsafe {
+            core::ptr::write_bytes(host_addr as *mut u8, 0, num_bytes);
+        }
+        let completion = unsafe { self.map(IoOperation::BusMasterCommonBuffer, host_addr, num_bytes) }
+            .inspect_err(|_| {
+                // The mapping failed, so nothing else references the pages
+                let _ = unsafe { self.free_buffer(pages, host_addr) };
+            });
+        let mapping = crate::try_completion!(status, completion);
+        status.into_with_val(|| MappingEx {
+            mapping: Some(mapping),
+            pci: self,
+            buffer: Buffer::Common { ptr, pages },
+            direction: PhantomData
+        })
+    }
+
+    /// Allocate pages suitable for a `BusMasterCommonBuffer` mapping.
+    ///
+    /// The memory type must be either `BOOT_SERVICES_DATA` or
+    /// `RUNTIME_SERVICES_DATA`. Only the `MEMORY_WRITE_COMBINE`,
+    /// `MEMORY_CACHED` and `DUAL_ADDRESS_CYCLE` attributes are allowed.
+    pub fn allocate_buffer(&self, mem_ty: MemoryType, pages: usize, attributes: PciAttributes) -> Result<*mut c_void> {
+        let mut host_addr = core::ptr::null_mut();
+        // Only AllocateAnyPages is supported by this function
+        crate::efi_call!((self.allocate_buffer)(self, 0, mem_ty, pages, &mut host_addr, attributes.bits()) => host_addr)
+    }
+
+    /// Free pages allocated by `allocate_buffer`.
+    ///
+    /// # Safety
+    ///
+    /// The pages must have been allocated by `allocate_buffer` on the same
+    /// controller and must no longer be mapped or otherwise in use.
+    pub unsafe fn free_buffer(&self, pages: usize, host_addr: *mut c_void) -> Result {
+        crate::efi_call!((self.free_buffer)(self, pages, host_addr))
+    }
+
+    /// Remove device memory mapping for the previously mapped system address.
+    ///
+    /// With the `exts` feature, an event notification function which
+    /// preempted another `map` or `unmap` call fails with `NOT_READY`, and
+    /// gets the mapping back to release it later.
+    pub fn unmap(&self, mapping: Mapping) -> Result<(), Mapping> {
+        #[cfg(feature = "exts")]
+        let status = tracking::untrack(mapping.addr, || (self.unmap)(self, mapping.addr));
+        #[cfg(not(feature = "exts"))]
+        let status = (self.unmap)(self, mapping.addr);
+        status.into_with_err(|_| mapping)
+    }
+
+    /// Flushes all PCI controller specific transactions.
+    ///
+    /// Per the UEFI specification this flushes the posted write
+    /// transactions of bus masters from the PCI host bridge to system
+    /// memory, so that data written by the device through a
+    /// `BusMasterWrite` or `BusMasterCommonBuffer` mapping is visible to
+    /// the processor once this returns. It does not order the processor's
+    /// own memory-mapped I/O writes with respect to the device.
+    ///
+    /// Some implementations return `SUCCESS` without doing anything, while
+    /// others report a warning. The status returned by the firmware is
+    /// kept in the resulting `Completion`, so callers relying on the
+    /// flush for DMA ordering should check it rather than assume a clean
+    /// success.
+    pub fn flush(&self) -> Result {
+        crate::efi_call!((self.flush)(self))
+    }
+
+    /// Perform an operation on the attributes of the controller.
+    ///
+    /// The `attributes` argument is only used by the `Set`, `Enable` and
+    /// `Disable` operations. The returned attributes are only meaningful
+    /// for the `Get` and `Supported` operations, and are empty otherwise.
+    pub fn attributes(&self, op: AttributeOperation, attributes: PciAttributes) -> Result<PciAttributes> {
+        let mut result = 0u64;
+        let result_ptr = match op {
+            AttributeOperation::Get | AttributeOperation::Supported => &mut result as *mut u64,
+            _ => core::ptr::null_mut()
+        };
+        crate::efi_call!((self.attributes)(self, op, attributes.bits(), result_ptr) => PciAttributes::from_bits_truncate(result))
+    }
+
+    /// Query the attributes supported by the controller and the ones
+    /// which are currently enabled, in this order.
+    pub fn attribute_status(&self) -> Result<(PciAttributes, PciAttributes)> {
+        let mut status = Status::SUCCESS;
+        let supported = crate::try_completion!(status, self.attributes(AttributeOperation::Supported, PciAttributes::empty()));
+        let enabled = crate::try_completion!(status, self.attributes(AttributeOperation::Get, PciAttributes::empty()));
+        status.into_with_val(|| (supported, enabled))
+    }
+}
+
+#[cfg(feature = "exts")]
+impl PciIO {
+    /// Iterate over every PCI controller known to the firmware.
+    ///
+    /// Handles whose protocol interface cannot be retrieved are skipped.
+    /// The interfaces are obtained with `HandleProtocol`, so this is meant
+    /// for enumeration tools rather than drivers, which should open the
+    /// protocol on the controllers they manage with `open_protocol`.
+    pub fn all(bt: &BootServices) -> Result<impl Iterator<Item = (Handle, &PciIO)> + '_> {
+        bt.find_handles::<PciIO>()
+            .map_inner(move |handles| {
+                handles.into_iter().filter_map(move |handle| {
+                    let pci = bt.handle_protocol::<PciIO>(handle).ok()?.log();
+                    Some((handle, unsafe { &*pci.get() }))
+                })
+            })
+    }
+}
+
+/// Size of the pages allocated by `PciIO::allocate_buffer`
+const PAGE_SIZE: usize = 4096;
+
+bitflags! {
+    /// Attributes of a PCI controller, also used to qualify the memory
+    /// allocated for common buffer mappings.
+    pub struct PciAttributes: u64 {
+        /// ISA I/O cycles 0x0000-0x00ff, 0x0100-0x03ff aliased
+        const ISA_MOTHERBOARD_IO = 0x0001;
+        /// ISA I/O cycles 0x0100-0x03ff, 10 bit decode
+        const ISA_IO = 0x0002;
+        /// VGA palette I/O cycles 0x3c6, 0x3c8 and 0x3c9, 10 bit decode
+        const VGA_PALETTE_IO = 0x0004;
+        /// VGA frame buffer memory cycles 0xa0000-0xbffff
+        const VGA_MEMORY = 0x0008;
+        /// VGA I/O cycles 0x3b0-0x3bb and 0x3c0-0x3df, 10 bit decode
+        const VGA_IO = 0x0010;
+        /// Primary IDE controller I/O cycles
+        const IDE_PRIMARY_IO = 0x0020;
+        /// Secondary IDE controller I/O cycles
+        const IDE_SECONDARY_IO = 0x0040;
+        /// Memory region can be write combined
+        const MEMORY_WRITE_COMBINE = 0x0080;
+        /// I/O cycles are decoded
+        const IO = 0x0100;
+        /// Memory cycles are decoded
+        const MEMORY = 0x0200;
+        /// Controller may act as a bus master
+        const BUS_MASTER = 0x0400;
+        /// Memory region can be cached
+        const MEMORY_CACHED = 0x0800;
+        /// Memory region can be disabled
+        const MEMORY_DISABLE = 0x1000;
+        /// Controller is embedded on the system board
+        const EMBEDDED_DEVICE = 0x2000;
+        /// Option ROM is embedded on the system board
+        const EMBEDDED_ROM = 0x4000;
+        /// Controller can generate 64 bit DMA addresses
+        const DUAL_ADDRESS_CYCLE = 0x8000;
+        /// ISA I/O cycles 0x0100-0x03ff, 16 bit decode
+        const ISA_IO_16 = 0x10000;
+        /// VGA palette I/O cycles, 16 bit decode
+        const VGA_PALETTE_IO_16 = 0x20000;
+        /// VGA I/O cycles, 16 bit decode
+        const VGA_IO_16 = 0x40000;
+    }
+}
+
+/// Operation performed by `PciIO::attributes`.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AttributeOperation {
+    /// Retrieve the attributes which are currently enabled.
+    Get,
+    /// Replace the enabled attributes with the given ones.
+    Set,
+    /// Enable the given attributes, keeping the other ones.
+    Enable,
+    /// Disable the given attributes, keeping the other ones.
+    Disable,
+    /// Retrieve the attributes supported by the controller.
+    Supported
+}
+
+newtype_enum! {
+    /// An index of the PCI Base Address Register.
+    #[derive(Hash, Ord, PartialOrd)]
+    pub enum IoRegister: u8 => {
+        R0 = 0,
+        R1 = 1,
+        R2 = 2,
+        R3 = 3,
+        R4 = 4,
+        R5 = 5,
+        PASS_THROUGH_BAR = 0xff,
+    }
+}
+
+/// Indicator of the upcoming bus master operation.
+/// The bus master is going to read or write to system memory.
+/// Or both.
+#[repr(i32)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IoOperation {
+    /// A read operation from system memory by a bus master.
+    BusMasterRead,
+    /// A write operation to system memory by a bus master.
+    BusMasterWrite,
+    /// Provides both read and write access to system memory
+    /// by both the processor and a bus master. The buffer
+    /// is coherent from both the processor’s and the bus
+    /// master’s point of view.
+    BusMasterCommonBuffer
+}
+
+impl IoOperation {
+    /// Convert the raw value of an operation received over FFI.
+    ///
+    /// The enum is exhaustive, so `map` only ever passes one of the three
+    /// operations defined by the specification to the firmware: a value
+    /// of this type holding another discriminant is undefined behavior
+    /// before it ever reaches the protocol. Raw values must go through
+    /// this function instead, which fails with `INVALID_PARAMETER` for
+    /// values out of range.
+    pub fn from_raw(raw: i32) -> Result<IoOperation> {
+        match raw {
+            0 => Ok(IoOperation::BusMasterRead.into()),
+            1 => Ok(IoOperation::BusMasterWrite.into()),
+            2 => Ok(IoOperation::BusMasterCommonBuffer.into()),
+            _ => Err(Status::INVALID_PARAMETER.into())
+        }
+    }
+}
+
+/// Test doubles for code using `PciIO` without real firmware.
+///
+/// `PciIO::mock()` builds a protocol instance on which every entry point
+/// fails with `UNSUPPORTED`, and the `with_*` methods replace some of
+/// them with functions provided by the test.
+#[cfg(feature = "test-utils")]
+impl PciIO {
+    /// Create a protocol instance which does not support any operation.
+    pub fn mock() -> PciIO {
+        PciIO {
+            poll_mem: 0,
+            poll_io: 0,
+            mem: IoSpace {
+                read: mock::io_read,
+                write: mock::io_write
+            },
+            io: IoSpace {
+                read: mock::io_read,
+                write: mock::io_write
+            },
+            config: ConfigSpace {
+                read: mock::config_read,
+                write: mock::config_write
+            },
+            copy_mem: 0,
+            map: mock::map,
+            unmap: mock::unmap,
+            allocate_buffer: mock::allocate_buffer,
+            free_buffer: mock::free_buffer,
+            flush: mock::flush,
+            get_location: mock::get_location,
+            attributes: mock::attributes,
+            get_bar_attributes: 0,
+            set_bar_attributes: 0,
+            rom_size_bytes: 0,
+            rom_image: core::ptr::null(),
+        }
+    }
+
+    /// Replace the memory-mapped I/O accessors.
+    pub fn with_mem(mut self, read: IoReadFn, write: IoWriteFn) -> PciIO {
+        self.mem = IoSpace { read, write };
+        self
+    }
+
+    /// Replace the I/O port space accessors.
+    pub fn with_io(mut self, read: IoReadFn, write: IoWriteFn) -> PciIO {
+        self.io = IoSpace { read, write };
+        self
+    }
+
+    /// Replace the configuration space accessors.
+    pub fn with_config(mut self, read: ConfigReadFn, write: ConfigWriteFn) -> PciIO {
+        self.config = ConfigSpace { read, write };
+        self
+    }
+
+    /// Replace the DMA mapping functions.
+    pub fn with_map(mut self, map: MapFn, unmap: UnmapFn) -> PciIO {
+        self.map = map;
+        self.unmap = unmap;
+        self
+    }
+
+    /// Replace the common buffer allocation functions.
+    pub fn with_buffer(mut self, allocate_buffer: AllocateBufferFn, free_buffer: FreeBufferFn) -> PciIO {
+        self.allocate_buffer = allocate_buffer;
+        self.free_buffer = free_buffer;
+        self
+    }
+
+    /// Replace the flush function.
+    pub fn with_flush(mut self, flush: FlushFn) -> PciIO {
+        self.flush = flush;
+        self
+    }
+
+    /// Replace the attributes function.
+    pub fn with_attributes(mut self, attributes: AttributesFn) -> PciIO {
+        self.attributes = attributes;
+        self
+    }
+
+    /// Replace the location function.
+    pub fn with_location(mut self, get_location: GetLocationFn) -> PciIO {
+        self.get_location = get_location;
+        self
+    }
+}
+
+/// Default entry points of `PciIO::mock()`.
+#[cfg(feature = "test-utils")]
+mod mock {
+    use super::{AttributeOperation, IoOperation, IoRegister, IoWidth, PciIO};
+    use crate::table::boot::MemoryType;
+    use crate::Status;
+    use core::ffi::c_void;
+
+    pub extern "efiapi" fn io_read(_: &PciIO, _: IoWidth, _: IoRegister, _: u64, _: usize, _: *mut u8) -> Status {
+        Status::UNSUPPORTED
+    }
+
+    pub extern "efiapi" fn io_write(_: &PciIO, _: IoWidth, _: IoRegister, _: u64, _: usize, _: *const u8) -> Status {
+        Status::UNSUPPORTED
+    }
+
+    pub extern "efiapi" fn config_read(_: &PciIO, _: IoWidth, _: u32, _: usize, _: *mut u8) -> Status {
+        Status::UNSUPPORTED
+    }
+
+    pub extern "efiapi" fn config_write(_: &PciIO, _: IoWidth, _: u32, _: usize, _: *const u8) -> Status {
+        Status::UNSUPPORTED
+    }
+
+    pub extern "efiapi" fn map(_: &PciIO, _: IoOperation, _: *const c_void, _: &mut usize, _: &mut u64, _: &mut *const c_void) -> Status {
+        Status::UNSUPPORTED
+    }
+
+    pub extern "efiapi" fn unmap(_: &PciIO, _: *const c_void) -> Status {
+        Status::UNSUPPORTED
+    }
+
+    pub extern "efiapi" fn allocate_buffer(_: &PciIO, _: u32, _: MemoryType, _: usize, _: &mut *mut c_void, _: u64) -> Status {
+        Status::UNSUPPORTED
+    }
+
+    pub extern "efiapi" fn free_buffer(_: &PciIO, _: usize, _: *mut c_void) -> Status {
+        Status::UNSUPPORTED
+    }
+
+    pub extern "efiapi" fn flush(_: &PciIO) -> Status {
+        Status::UNSUPPORTED
+    }
+
+    pub extern "efiapi" fn attributes(_: &PciIO, _: AttributeOperation, _: u64, _: *mut u64) -> Status {
+        Status::UNSUPPORTED
+    }
+
+    pub extern "efiapi" fn get_location(_: &PciIO, _: &mut usize, _: &mut usize, _: &mut usize, _: &mut usize) -> Status {
+        Status::UNSUPPORTED
+    }
+}
+
+newtype_enum! {
+    pub enum IoIncrement: i32 => {
+        LOOP   = 0,
+        FIFO   = 4,
+        FILL   = 8,
+    }
+}
+
+newtype_enum! {
+    // U8        = 0,
+    // U16       = 1,
+    // U32       = 2,
+    // U64       = 3,
+    // FIFO_U8   = 4,
+    // FIFO_U16  = 5,
+    // FIFO_U32  = 6,
+    // FIFO_U64  = 7,
+    // FILL_U8   = 8,
+    // FILL_U16  = 9,
+    // FILL_U32  = 10,
+    // FILL_U64  = 11,
+    pub enum IoWidth: i32 => {
+        U8        = 0,
+        U16       = 1,
+        U32       = 2,
+        U64       = 3,
+    }
+}
+
+impl IoWidth {
+    /// Number of bytes covered by a single element of this width: 1, 2, 4
+    /// or 8, or `None` if the width is not one defined by the specification.
+    ///
+    /// The FIFO and fill widths transfer elements of the same size as the
+    /// corresponding plain widths.
+    pub fn byte_size(&self) -> Option<usize> {
+        if (0..IO_WIDTH_COUNT).contains(&self.0) {
+            Some(1 << (self.0 & 3))
+        } else {
+            None
+        }
+    }
+
+    /// Whether this is one of the plain `U8`, `U16`, `U32` and `U64` widths
+    fn is_plain(self) -> bool {
+        (IoWidth::U8.0..=IoWidth::U64.0).contains(&self.0)
+    }
+}
+
+/// Number of widths defined by the specification, from `U8` to `FILL_U64`
+const IO_WIDTH_COUNT: i32 = 12;
@@ -0,0 +1,107 @@
+//! Power management capability.
+
+use super::{Capability, CapabilityId, PciIO};
+use crate::table::boot::BootServices;
+use crate::{Result, Status};
+
+/// Offset of the Power Management Capabilities register (PMC)
+const PM_CAPABILITIES: u32 = 2;
+/// Offset of the Power Management Control/Status register (PMCSR)
+const PM_CONTROL_STATUS: u32 = 4;
+
+/// PMC bit advertising support for the D1 state
+const PMC_D1_SUPPORT: u16 = 1 << 9;
+/// PMC bit advertising support for the D2 state
+const PMC_D2_SUPPORT: u16 = 1 << 10;
+
+/// PMCSR field holding the current power state
+const PMCSR_POWER_STATE: u16 = 0x3;
+/// PMCSR PME status bit, which is cleared by writing one to it
+const PMCSR_PME_STATUS: u16 = 1 << 15;
+
+/// Device power state, as defined by the PCI Power Management specification.
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PowerState {
+    /// Fully operational state.
+    D0 = 0,
+    /// Light sleep state, optional.
+    D1 = 1,
+    /// Deeper sleep state, optional.
+    D2 = 2,
+    /// Deepest state which can be reached without removing power.
+    D3Hot = 3,
+}
+
+impl PowerState {
+    fn from_pmcsr(pmcsr: u16) -> PowerState {
+        match pmcsr & PMCSR_POWER_STATE {
+            0 => PowerState::D0,
+            1 => PowerState::D1,
+            2 => PowerState::D2,
+            _ => PowerState::D3Hot,
+        }
+    }
+
+    /// Minimal recovery time in microseconds, mandated by the specification,
+    /// before the function may be accessed after a transition between states.
+    fn transition_delay(from: PowerState, to: PowerState) -> usize {
+        match (from, to) {
+            (PowerState::D3Hot, _) | (_, PowerState::D3Hot) => 10_000,
+            (PowerState::D2, _) | (_, PowerState::D2) => 200,
+            _ => 0,
+        }
+    }
+}
+
+impl PciIO {
+    /// Locate the power management capability of the controller.
+    ///
+    /// Fails with `UNSUPPORTED` if the controller does not have one.
+    fn power_management_capability(&self) -> Result<Capability> {
+        let mut status = Status::SUCCESS;
+        match crate::try_completion!(status, self.config().find_capability(CapabilityId::POWER_MANAGEMENT)) {
+            Some(cap) => status.into_with_val(|| cap),
+            None => Err(Status::UNSUPPORTED.into()),
+        }
+    }
+
+    /// Query the current power state of the controller.
+    pub fn power_state(&self) -> Result<PowerState> {
+        let mut status = Status::SUCCESS;
+        let cap = crate::try_completion!(status, self.power_management_capability());
+        let pmcsr = crate::try_completion!(status, self.read_config_single::<u16>(cap.offset + PM_CONTROL_STATUS));
+        status.into_with_val(|| PowerState::from_pmcsr(pmcsr))
+    }
+
+    /// Transition the controller to another power state.
+    ///
+    /// The boot services are used to wait for the recovery time that
+    /// the specification mandates after each transition, before the
+    /// controller may be accessed again.
+    ///
+    /// Fails with `UNSUPPORTED` if the controller has no power management
+    /// capability or does not support the optional D1/D2 state requested.
+    pub fn set_power_state(&self, bt: &BootServices, state: PowerState) -> Result {
+        let mut status = Status::SUCCESS;
+        let cap = crate::try_completion!(status, self.power_management_capability());
+        let pmc = crate::try_completion!(status, self.read_config_single::<u16>(cap.offset + PM_CAPABILITIES));
+        let supported = match state {
+            PowerState::D1 => pmc & PMC_D1_SUPPORT != 0,
+            PowerState::D2 => pmc & PMC_D2_SUPPORT != 0,
+            _ => true,
+        };
+        if !supported {
+            return Err(Status::UNSUPPORTED.into());
+        }
+        let pmcsr = crate::try_completion!(status, self.read_config_single::<u16>(cap.offset + PM_CONTROL_STATUS));
+        let current = PowerState::from_pmcsr(pmcsr);
+        if current != state {
+            // Do not write back the PME status, which would clear it
+            let pmcsr = (pmcsr & !(PMCSR_POWER_STATE | PMCSR_PME_STATUS)) | state as u16;
+            crate::try_completion!(status, self.write_config(cap.offset + PM_CONTROL_STATUS, &[pmcsr]));
+            bt.stall(PowerState::transition_delay(current, state));
+        }
+        status.into()
+    }
+}
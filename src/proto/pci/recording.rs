@@ -0,0 +1,207 @@
+//! Test double recording every access made through the protocol.
+
+use super::{IoRegister, IoWidth, PciIO};
+use crate::Status;
+use alloc_api::{collections::BTreeMap, vec::Vec};
+use core::cell::{Ref, RefCell};
+
+/// Address space targeted by a recorded access
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum AccessSpace {
+    /// Memory-mapped I/O region of a BAR
+    Memory(IoRegister),
+    /// I/O port region of a BAR
+    Io(IoRegister),
+    /// Configuration space
+    Config,
+}
+
+/// Direction of a recorded access
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AccessKind {
+    /// The value was read from the device
+    Read,
+    /// The value was written to the device
+    Write,
+}
+
+/// A single element read or written through the protocol.
+///
+/// Calls transferring several elements are recorded as one access per
+/// element, at the offset the element was transferred to or from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Access {
+    /// Direction of the access
+    pub kind: AccessKind,
+    /// Address space of the access
+    pub space: AccessSpace,
+    /// Width requested by the caller
+    pub width: IoWidth,
+    /// Offset of the element
+    pub offset: u64,
+    /// Value of the element, zero-extended
+    pub value: u64,
+}
+
+/// PciIO protocol backed by a sparse register file, which keeps a log of
+/// every memory, I/O and configuration space access.
+///
+/// Writes are stored in the register file and reads return its contents,
+/// so registers which are never written read as zero unless preset with
+/// `preset`. The other functions of the protocol are the `PciIO::mock`
+/// stubs. The protocol must stay the first field: the functions recover
+/// the recorder from the `this` pointer they are given.
+#[repr(C)]
+pub struct RecordingPciIO {
+    pci: PciIO,
+    state: RefCell<State>,
+}
+
+#[derive(Default)]
+struct State {
+    accesses: Vec<Access>,
+    registers: BTreeMap<(AccessSpace, u64), u8>,
+}
+
+impl RecordingPciIO {
+    /// Create a recorder with an empty log and an all-zero register file.
+    pub fn new() -> RecordingPciIO {
+        RecordingPciIO {
+            pci: PciIO::mock()
+                .with_mem(mem_read, mem_write)
+                .with_io(io_read, io_write)
+                .with_config(config_read, config_write),
+            state: RefCell::new(State::default())
+        }
+    }
+
+    /// Protocol interface to hand to the code under test.
+    pub fn pci(&self) -> &PciIO {
+        &self.pci
+    }
+
+    /// Set the contents of the register file, without recording an access.
+    pub fn preset(&self, space: AccessSpace, offset: u64, bytes: &[u8]) {
+        let mut state = self.state.borrow_mut();
+        for (i, &byte) in bytes.iter().enumerate() {
+            state.registers.insert((space, offset + i as u64), byte);
+        }
+    }
+
+    /// Accesses recorded so far, oldest first.
+    pub fn accesses(&self) -> Ref<'_, [Access]> {
+        Ref::map(self.state.borrow(), |state| &state.accesses[..])
+    }
+
+    /// Take the accesses recorded so far, leaving the log empty.
+    pub fn take_accesses(&self) -> Vec<Access> {
+        core::mem::take(&mut self.state.borrow_mut().accesses)
+    }
+
+    /// SAFETY: `this` must be the `pci` field of a `RecordingPciIO`, which
+    /// holds as long as the functions below are only installed by `new`.
+    unsafe fn from_pci(this: &PciIO) -> &RecordingPciIO {
+        &*(this as *const PciIO as *const RecordingPciIO)
+    }
+
+    fn read(&self, space: AccessSpace, width: IoWidth, offset: u64, count: usize, buffer: *mut u8) -> Status {
+        let size = match width.byte_size() {
+            Some(size) => size,
+            None => return Status::INVALID_PARAMETER
+        };
+        let mut state = self.state.borrow_mut();
+        for i in 0..count {
+            let (offset, position) = element_position(width, offset, i, size);
+            let mut bytes = [0u8; 8];
+            for (j, byte) in bytes[..size].iter_mut().enumerate() {
+                *byte = state.registers.get(&(space, offset + j as u64)).copied().unwrap_or(0);
+            }
+            unsafe {
+                core::ptr::copy_nonoverlapping(bytes.as_ptr(), buffer.add(position), size);
+            }
+            state.accesses.push(Access {
+                kind: AccessKind::Read,
+                space,
+                width,
+                offset,
+                value: u64::from_le_bytes(bytes)
+            });
+        }
+        Status::SUCCESS
+    }
+
+    fn write(&self, space: AccessSpace, width: IoWidth, offset: u64, count: usize, buffer: *const u8) -> Status {
+        let size = match width.byte_size() {
+            Some(size) => size,
+            None => return Status::INVALID_PARAMETER
+        };
+        let mut state = self.state.borrow_mut();
+        for i in 0..count {
+            let (offset, position) = element_position(width, offset, i, size);
+            let mut bytes = [0u8; 8];
+            unsafe {
+                core::ptr::copy_nonoverlapping(buffer.add(position), bytes.as_mut_ptr(), size);
+            }
+            for (j, &byte) in bytes[..size].iter().enumerate() {
+                state.registers.insert((space, offset + j as u64), byte);
+            }
+            state.accesses.push(Access {
+                kind: AccessKind::Write,
+                space,
+                width,
+                offset,
+                value: u64::from_le_bytes(bytes)
+            });
+        }
+        Status::SUCCESS
+    }
+}
+
+impl Default for RecordingPciIO {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Device offset and buffer position of the element at `index`
+///
+/// FIFO widths keep accessing the same register, while fill widths keep
+/// transferring the first element of the buffer.
+fn element_position(width: IoWidth, offset: u64, index: usize, size: usize) -> (u64, usize) {
+    let step = index * size;
+    match width.0 >> 2 {
+        0 => (offset + step as u64, step),
+        1 => (offset, step),
+        _ => (offset + step as u64, 0)
+    }
+}
+
+extern "efiapi" fn mem_read(this: &PciIO, width: IoWidth, bar: IoRegister, offset: u64, count: usize, buffer: *mut u8) -> Status {
+    let recorder = unsafe { RecordingPciIO::from_pci(this) };
+    recorder.read(AccessSpace::Memory(bar), width, offset, count, buffer)
+}
+
+extern "efiapi" fn mem_write(this: &PciIO, width: IoWidth, bar: IoRegister, offset: u64, count: usize, buffer: *const u8) -> Status {
+    let recorder = unsafe { RecordingPciIO::from_pci(this) };
+    recorder.write(AccessSpace::Memory(bar), width, offset, count, buffer)
+}
+
+extern "efiapi" fn io_read(this: &PciIO, width: IoWidth, bar: IoRegister, offset: u64, count: usize, buffer: *mut u8) -> Status {
+    let recorder = unsafe { RecordingPciIO::from_pci(this) };
+    recorder.read(AccessSpace::Io(bar), width, offset, count, buffer)
+}
+
+extern "efiapi" fn io_write(this: &PciIO, width: IoWidth, bar: IoRegister, offset: u64, count: usize, buffer: *const u8) -> Status {
+    let recorder = unsafe { RecordingPciIO::from_pci(this) };
+    recorder.write(AccessSpace::Io(bar), width, offset, count, buffer)
+}
+
+extern "efiapi" fn config_read(this: &PciIO, width: IoWidth, offset: u32, count: usize, buffer: *mut u8) -> Status {
+    let recorder = unsafe { RecordingPciIO::from_pci(this) };
+    recorder.read(AccessSpace::Config, width, offset.into(), count, buffer)
+}
+
+extern "efiapi" fn config_write(this: &PciIO, width: IoWidth, offset: u32, count: usize, buffer: *const u8) -> Status {
+    let recorder = unsafe { RecordingPciIO::from_pci(this) };
+    recorder.write(AccessSpace::Config, width, offset.into(), count, buffer)
+}
@@ -0,0 +1,68 @@
+//! Function Level Reset.
+
+use super::{Capability, CapabilityId, PciIO};
+use crate::table::boot::BootServices;
+use crate::{Result, Status};
+
+/// Offset of the Device Capabilities register in the PCI Express capability
+const PCIE_DEVICE_CAPABILITIES: u32 = 0x04;
+/// Offset of the Device Control register in the PCI Express capability
+const PCIE_DEVICE_CONTROL: u32 = 0x08;
+/// Offset of the Device Status register in the PCI Express capability
+const PCIE_DEVICE_STATUS: u32 = 0x0a;
+
+/// Device Capabilities bit advertising support for Function Level Reset
+const DEVCAP_FLR: u32 = 1 << 28;
+/// Device Control bit initiating a Function Level Reset
+const DEVCTL_INITIATE_FLR: u16 = 1 << 15;
+/// Device Status bit indicating non-posted requests are pending
+const DEVSTA_TRANSACTIONS_PENDING: u16 = 1 << 5;
+
+/// Time the function is given to complete the reset, and to complete its
+/// pending transactions beforehand, in microseconds
+const FLR_DELAY_US: usize = 100_000;
+
+impl PciIO {
+    /// Locate the PCI Express capability of the controller.
+    ///
+    /// Fails with `UNSUPPORTED` if the controller does not have one.
+    fn express_capability(&self) -> Result<Capability> {
+        let mut status = Status::SUCCESS;
+        match crate::try_completion!(status, self.config().find_capability(CapabilityId::PCI_EXPRESS)) {
+            Some(cap) => status.into_with_val(|| cap),
+            None => Err(Status::UNSUPPORTED.into()),
+        }
+    }
+
+    /// Reset the function through the PCI Express capability.
+    ///
+    /// Pending transactions are given up to 100 ms to complete, then the
+    /// reset is initiated and the boot services are used to wait for the
+    /// 100 ms the specification mandates before the function may be
+    /// accessed again. The configuration space is reset along with the
+    /// rest of the function, so the caller is responsible for saving and
+    /// restoring whatever it needs, for instance with `save_config` and
+    /// `restore_config`.
+    ///
+    /// Fails with `UNSUPPORTED` if the controller is not a PCI Express one
+    /// or does not support Function Level Reset.
+    pub fn function_level_reset(&self, bt: &BootServices) -> Result {
+        let mut status = Status::SUCCESS;
+        let cap = crate::try_completion!(status, self.express_capability());
+        let devcap = crate::try_completion!(status, self.read_config_single::<u32>(cap.offset + PCIE_DEVICE_CAPABILITIES));
+        if devcap & DEVCAP_FLR == 0 {
+            return Err(Status::UNSUPPORTED.into());
+        }
+        // Transactions still pending after the delay are lost in the reset
+        let pending = self.poll_config(bt, cap.offset + PCIE_DEVICE_STATUS, DEVSTA_TRANSACTIONS_PENDING, 0, FLR_DELAY_US as u64 * 10)
+            .or_else(|error| match error.status() {
+                Status::TIMEOUT => Ok(DEVSTA_TRANSACTIONS_PENDING.into()),
+                _ => Err(error),
+            });
+        crate::try_completion!(status, pending);
+        let devctl = crate::try_completion!(status, self.read_config_single::<u16>(cap.offset + PCIE_DEVICE_CONTROL));
+        crate::try_completion!(status, self.write_config(cap.offset + PCIE_DEVICE_CONTROL, &[devctl | DEVCTL_INITIATE_FLR]));
+        bt.stall(FLR_DELAY_US);
+        status.into()
+    }
+}
@@ -0,0 +1,332 @@
+//! PCI expansion ROM images.
+
+use super::{ConfigRegister, PciIO};
+use crate::{Result, Status};
+use core::convert::TryInto;
+
+#[cfg(feature = "exts")]
+use super::IoRegister;
+#[cfg(feature = "exts")]
+use alloc_api::vec::Vec;
+
+/// Signature found at the start of every ROM image
+const ROM_SIGNATURE: u16 = 0xaa55;
+/// Signature of the PCI data structure
+const PCIR_SIGNATURE: [u8; 4] = *b"PCIR";
+/// Signature of the EFI specific fields of the ROM header
+const EFI_SIGNATURE: u32 = 0x0ef1;
+
+/// Offset of the pointer to the PCI data structure in the ROM header
+const ROM_PCIR_OFFSET: usize = 0x18;
+/// Size of the PCI data structure
+const PCIR_SIZE: usize = 0x18;
+/// Indicator bit marking the last image of the ROM
+const PCIR_LAST_IMAGE: u8 = 0x80;
+/// Unit of the image length field
+const IMAGE_BLOCK_SIZE: usize = 512;
+
+/// Bit of the Expansion ROM Base Address register enabling the decode
+const ROM_BAR_ENABLE: u32 = 1 << 0;
+/// Address bits of the Expansion ROM Base Address register
+#[cfg(feature = "exts")]
+const ROM_BAR_ADDRESS: u32 = !0x7ff;
+/// Largest expansion ROM which can be decoded by a controller
+#[cfg(feature = "exts")]
+const MAX_ROM_SIZE: usize = 16 << 20;
+
+impl PciIO {
+    /// The copy of the option ROM made by the PCI bus driver, if the
+    /// controller has one.
+    pub fn option_rom(&self) -> Option<OptionRom<'_>> {
+        if self.rom_image.is_null() || self.rom_size_bytes == 0 {
+            return None;
+        }
+        let data = unsafe { core::slice::from_raw_parts(self.rom_image as *const u8, self.rom_size_bytes as usize) };
+        Some(OptionRom::new(data))
+    }
+
+    /// Enable the decode of the expansion ROM by the controller.
+    ///
+    /// This sets the enable bit of the Expansion ROM Base Address register
+    /// of a type 0 configuration header. The ROM is then readable at the
+    /// address held by the register, provided the memory space decode is
+    /// enabled in the command register. This does not affect `option_rom`,
+    /// which is the copy made by the PCI bus driver while enumerating.
+    pub fn enable_rom(&self) -> Result {
+        self.update_rom_bar(|register| register | ROM_BAR_ENABLE)
+    }
+
+    /// Disable the decode of the expansion ROM by the controller.
+    pub fn disable_rom(&self) -> Result {
+        self.update_rom_bar(|register| register & !ROM_BAR_ENABLE)
+    }
+
+    fn update_rom_bar(&self, f: impl FnOnce(u32) -> u32) -> Result {
+        let mut status = Status::SUCCESS;
+        let register = crate::try_completion!(status, self.read_config_single::<u32>(ConfigRegister::EXPANSION_ROM.0));
+        crate::try_completion!(status, self.write_config(ConfigRegister::EXPANSION_ROM.0, &[f(register)]));
+        status.into()
+    }
+
+    /// Copy the expansion ROM straight from the controller.
+    ///
+    /// This is useful for controllers whose ROM was not copied by the PCI
+    /// bus driver, so that `option_rom` returns `None`. The decode is
+    /// enabled for the duration of the copy, then the Expansion ROM Base
+    /// Address register is restored. Images are copied until the one marked
+    /// as last, or until an invalid one. Fails with `NOT_FOUND` if no
+    /// address is assigned to the ROM or if its first image is invalid.
+    #[cfg(feature = "exts")]
+    pub fn read_rom_bytes(&self) -> Result<Vec<u8>> {
+        let mut status = Status::SUCCESS;
+        let register = crate::try_completion!(status, self.read_config_single::<u32>(ConfigRegister::EXPANSION_ROM.0));
+        let base = register & ROM_BAR_ADDRESS;
+        if base == 0 {
+            return Err(Status::NOT_FOUND.into());
+        }
+        crate::try_completion!(status, self.write_config(ConfigRegister::EXPANSION_ROM.0, &[register | ROM_BAR_ENABLE]));
+        let copy = self.copy_rom(u64::from(base));
+        // Restore the register whether the copy succeeded or not
+        let restore = self.write_config(ConfigRegister::EXPANSION_ROM.0, &[register]);
+        let data = crate::try_completion!(status, copy);
+        crate::try_completion!(status, restore);
+        if data.is_empty() {
+            return Err(Status::NOT_FOUND.into());
+        }
+        status.into_with_val(|| data)
+    }
+
+    #[cfg(feature = "exts")]
+    fn copy_rom(&self, base: u64) -> Result<Vec<u8>> {
+        let mut status = Status::SUCCESS;
+        let mut data = Vec::new();
+        while data.len() < MAX_ROM_SIZE {
+            let start = data.len();
+            // The PCI data structure is usually in the first block
+            crate::try_completion!(status, self.copy_rom_blocks(base, &mut data, start + IMAGE_BLOCK_SIZE));
+            let pcir_end = read_u16(&data[start..], ROM_PCIR_OFFSET).unwrap() as usize + PCIR_SIZE;
+            if pcir_end > IMAGE_BLOCK_SIZE && start + pcir_end <= MAX_ROM_SIZE {
+                crate::try_completion!(status, self.copy_rom_blocks(base, &mut data, start + pcir_end));
+            }
+            let (length, last) = match RomImage::parse(&data[start..]) {
+                Some(image) => (image.size(), image.is_last()),
+                None => {
+                    data.truncate(start);
+                    break;
+                }
+            };
+            if start + length > MAX_ROM_SIZE {
+                data.truncate(start);
+                break;
+            }
+            crate::try_completion!(status, self.copy_rom_blocks(base, &mut data, start + length));
+            data.truncate(start + length);
+            if last {
+                break;
+            }
+        }
+        status.into_with_val(|| data)
+    }
+
+    /// Append the ROM blocks covering `data` up to `end`, reading them with
+    /// dword accesses as some controllers do not decode narrower ones.
+    #[cfg(feature = "exts")]
+    fn copy_rom_blocks(&self, base: u64, data: &mut Vec<u8>, end: usize) -> Result {
+        let mut status = Status::SUCCESS;
+        let mut block = [0u32; IMAGE_BLOCK_SIZE / 4];
+        while data.len() < end {
+            let offset = base + data.len() as u64;
+            crate::try_completion!(status, self.read_mem(IoRegister::PASS_THROUGH_BAR, offset, &mut block));
+            data.extend(block.iter().flat_map(|dword| dword.to_le_bytes()));
+        }
+        status.into()
+    }
+}
+
+/// Contents of a PCI expansion ROM.
+#[derive(Clone, Copy, Debug)]
+pub struct OptionRom<'a> {
+    data: &'a [u8]
+}
+
+impl<'a> OptionRom<'a> {
+    /// Wrap the raw bytes of an expansion ROM.
+    pub fn new(data: &'a [u8]) -> Self {
+        OptionRom { data }
+    }
+
+    /// Raw bytes of the whole ROM.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Iterate over the images stored in the ROM.
+    ///
+    /// Iteration stops after the image marked as last, or at the first
+    /// image with an invalid header or PCI data structure.
+    pub fn images(&self) -> RomImages<'a> {
+        RomImages {
+            data: self.data,
+            offset: 0,
+            done: false
+        }
+    }
+}
+
+/// Iterator over the images of an `OptionRom`.
+#[derive(Clone, Debug)]
+pub struct RomImages<'a> {
+    data: &'a [u8],
+    offset: usize,
+    done: bool
+}
+
+impl<'a> Iterator for RomImages<'a> {
+    type Item = RomImage<'a>;
+
+    fn next(&mut self) -> Option<RomImage<'a>> {
+        if self.done {
+            return None;
+        }
+        let image = RomImage::parse(&self.data[self.offset..]);
+        match image {
+            Some(image) if !image.is_last() && image.length < self.data.len() - self.offset => {
+                self.offset += image.length;
+            }
+            _ => self.done = true
+        }
+        image
+    }
+}
+
+/// One image of an expansion ROM, described by its PCI data structure.
+#[derive(Clone, Copy, Debug)]
+pub struct RomImage<'a> {
+    data: &'a [u8],
+    pcir: usize,
+    length: usize
+}
+
+impl<'a> RomImage<'a> {
+    fn parse(data: &'a [u8]) -> Option<Self> {
+        if read_u16(data, 0)? != ROM_SIGNATURE {
+            return None;
+        }
+        let pcir = read_u16(data, ROM_PCIR_OFFSET)? as usize;
+        if data.get(pcir..pcir + PCIR_SIZE)?[..4] != PCIR_SIGNATURE {
+            return None;
+        }
+        let length = read_u16(data, pcir + 0x10)? as usize * IMAGE_BLOCK_SIZE;
+        // The PCI data structure must lie within the image it describes
+        if pcir + PCIR_SIZE > length {
+            return None;
+        }
+        Some(RomImage {
+            data: &data[..core::cmp::min(length, data.len())],
+            pcir,
+            length
+        })
+    }
+
+    /// Vendor ID of the controller supported by this image.
+    pub fn vendor_id(&self) -> u16 {
+        self.pcir_u16(0x04)
+    }
+
+    /// Device ID of the controller supported by this image.
+    pub fn device_id(&self) -> u16 {
+        self.pcir_u16(0x06)
+    }
+
+    /// Revision of the PCI data structure.
+    pub fn revision(&self) -> u8 {
+        self.data[self.pcir + 0x0c]
+    }
+
+    /// Class code of the controller, in the same layout as in config space.
+    pub fn class_code(&self) -> [u8; 3] {
+        let offset = self.pcir + 0x0d;
+        [self.data[offset], self.data[offset + 1], self.data[offset + 2]]
+    }
+
+    /// Revision of the code of this image.
+    pub fn code_revision(&self) -> u16 {
+        self.pcir_u16(0x12)
+    }
+
+    /// Kind of code stored in this image.
+    pub fn code_type(&self) -> RomCodeType {
+        RomCodeType(self.data[self.pcir + 0x14])
+    }
+
+    /// Whether this is the last image of the ROM.
+    pub fn is_last(&self) -> bool {
+        self.data[self.pcir + 0x15] & PCIR_LAST_IMAGE != 0
+    }
+
+    /// Size of the image in bytes, as declared by its PCI data structure.
+    pub fn size(&self) -> usize {
+        self.length
+    }
+
+    /// Raw bytes of the image, truncated if the ROM is shorter than the
+    /// declared size.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// EFI specific fields of the ROM header, for images of type `EFI`.
+    pub fn efi_image(&self) -> Option<EfiRomImage<'a>> {
+        if self.code_type() != RomCodeType::EFI || read_u32(self.data, 0x04)? != EFI_SIGNATURE {
+            return None;
+        }
+        let offset = read_u16(self.data, 0x16)? as usize;
+        Some(EfiRomImage {
+            subsystem: read_u16(self.data, 0x08)?,
+            machine_type: read_u16(self.data, 0x0a)?,
+            compressed: read_u16(self.data, 0x0c)? != 0,
+            image: self.data.get(offset..)?
+        })
+    }
+
+    fn pcir_u16(&self, offset: usize) -> u16 {
+        read_u16(self.data, self.pcir + offset).unwrap()
+    }
+}
+
+/// The EFI driver embedded in a ROM image.
+#[derive(Clone, Copy, Debug)]
+pub struct EfiRomImage<'a> {
+    /// PE/COFF subsystem of the driver
+    pub subsystem: u16,
+    /// PE/COFF machine type of the driver
+    pub machine_type: u16,
+    /// Whether the driver is compressed with the EFI compression algorithm
+    pub compressed: bool,
+    /// Bytes of the driver, from its PE/COFF header to the end of the image
+    pub image: &'a [u8]
+}
+
+newtype_enum! {
+    /// Kind of code stored in a ROM image.
+    pub enum RomCodeType: u8 => {
+        /// Intel x86, PC-AT compatible
+        PC_AT = 0,
+        /// Open Firmware standard for PCI
+        OPEN_FIRMWARE = 1,
+        /// Hewlett-Packard PA RISC
+        HP_PA_RISC = 2,
+        /// Extensible Firmware Interface
+        EFI = 3,
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
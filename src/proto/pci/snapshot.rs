@@ -0,0 +1,67 @@
+//! Configuration space save and restore.
+
+use super::{ConfigRegister, PciIO};
+use crate::{Result, Status};
+
+/// Number of Base Address Registers of a type 0 header
+const BAR_COUNT: usize = 6;
+
+impl PciIO {
+    /// Save the registers of the header which a reset or a power state
+    /// transition clears.
+    ///
+    /// Only the layout of a type 0 header is known, so the snapshot is only
+    /// meaningful for endpoints. The prefetchable window upper halves,
+    /// expansion ROM and bridge control registers of a type 1 header are
+    /// not saved.
+    pub fn save_config(&self) -> Result<ConfigSnapshot> {
+        let mut status = Status::SUCCESS;
+        let command = crate::try_completion!(status, self.read_config_single::<u16>(ConfigRegister::COMMAND.0));
+        let cache_line_size = crate::try_completion!(status, self.cache_line_size());
+        let latency_timer = crate::try_completion!(status, self.latency_timer());
+        let mut bars = [0u32; BAR_COUNT];
+        crate::try_completion!(status, self.read_config(ConfigRegister::BAR0.0, &mut bars));
+        let expansion_rom = crate::try_completion!(status, self.read_config_single::<u32>(ConfigRegister::EXPANSION_ROM.0));
+        let interrupt_line = crate::try_completion!(status, self.read_config_single::<u8>(ConfigRegister::INTERRUPT_LINE.0));
+        status.into_with_val(|| ConfigSnapshot {
+            command,
+            cache_line_size,
+            latency_timer,
+            bars,
+            expansion_rom,
+            interrupt_line
+        })
+    }
+
+    /// Write back the registers saved by `save_config`.
+    ///
+    /// The command register is written last, so that the controller only
+    /// decodes its regions again once their addresses are restored.
+    pub fn restore_config(&self, snapshot: &ConfigSnapshot) -> Result {
+        let mut status = Status::SUCCESS;
+        crate::try_completion!(status, self.write_config(ConfigRegister::BAR0.0, &snapshot.bars));
+        crate::try_completion!(status, self.write_config(ConfigRegister::EXPANSION_ROM.0, &[snapshot.expansion_rom]));
+        crate::try_completion!(status, self.set_cache_line_size(snapshot.cache_line_size));
+        crate::try_completion!(status, self.set_latency_timer(snapshot.latency_timer));
+        crate::try_completion!(status, self.write_config(ConfigRegister::INTERRUPT_LINE.0, &[snapshot.interrupt_line]));
+        crate::try_completion!(status, self.write_config(ConfigRegister::COMMAND.0, &[snapshot.command]));
+        status.into()
+    }
+}
+
+/// Registers of the header saved by `PciIO::save_config`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ConfigSnapshot {
+    /// Command register
+    pub command: u16,
+    /// Cache line size register
+    pub cache_line_size: u8,
+    /// Latency timer register
+    pub latency_timer: u8,
+    /// Base Address Registers, including their read-only flag bits
+    pub bars: [u32; BAR_COUNT],
+    /// Expansion ROM base address register
+    pub expansion_rom: u32,
+    /// Interrupt line register
+    pub interrupt_line: u8,
+}
@@ -0,0 +1,109 @@
+//! Bookkeeping of the outstanding DMA mappings.
+//!
+//! Mapping the same host memory twice for conflicting operations leads to
+//! undefined results, which the firmware does not detect. Live mappings are
+//! recorded here so that `PciIO::map` can reject such requests.
+
+use super::{IoOperation, Mapping};
+use crate::{Completion, Result, Status};
+use alloc_api::vec::Vec;
+use core::cell::UnsafeCell;
+use core::ffi::c_void;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Host memory range covered by a live mapping
+struct Tracked {
+    mapping: *const c_void,
+    start: usize,
+    end: usize,
+    op: IoOperation,
+}
+
+/// List of live mappings, shared by all controllers.
+///
+/// Protocols may only be used from the bootstrap processor, so the list is
+/// never accessed from two processors at once. An event notification
+/// function may still preempt code updating the list and call `map` or
+/// `unmap` itself, which the `busy` flag detects.
+struct BspList {
+    busy: AtomicBool,
+    list: UnsafeCell<Vec<Tracked>>,
+}
+
+// SAFETY: the list is only accessed from the bootstrap processor, and only
+// through `with`, which rejects nested accesses from preempting code
+unsafe impl Sync for BspList {}
+
+impl BspList {
+    /// Run `f` on the list, or return `None` if the list is already in use
+    /// by the code preempted by the caller.
+    fn with<R>(&self, f: impl FnOnce(&mut Vec<Tracked>) -> R) -> Option<R> {
+        if self.busy.swap(true, Ordering::Acquire) {
+            return None;
+        }
+        // SAFETY: the flag was clear, so no other reference to the list is
+        // live, and any preempting code will find it set until `f` returns
+        let result = f(unsafe { &mut *self.list.get() });
+        self.busy.store(false, Ordering::Release);
+        Some(result)
+    }
+}
+
+/// Live mappings of all controllers.
+static MAPPINGS: BspList = BspList {
+    busy: AtomicBool::new(false),
+    list: UnsafeCell::new(Vec::new()),
+};
+
+/// Create a mapping with `map` unless it conflicts with a live mapping, and
+/// record it.
+///
+/// Devices may concurrently read from the same memory, any other
+/// combination of overlapping operations is a conflict and fails with
+/// `INVALID_PARAMETER`. Fails with `NOT_READY` if the list of mappings is
+/// in use by preempted code.
+pub(super) fn track(
+    host_addr: *const c_void,
+    num_bytes: usize,
+    op: IoOperation,
+    map: impl FnOnce() -> Result<Mapping>,
+) -> Result<Mapping> {
+    let start = host_addr as usize;
+    let end = start.saturating_add(num_bytes);
+    MAPPINGS
+        .with(|mappings| {
+            let conflicts = mappings.iter().any(|tracked| {
+                let overlaps = start < tracked.end && tracked.start < end;
+                let compatible = op == IoOperation::BusMasterRead && tracked.op == IoOperation::BusMasterRead;
+                overlaps && !compatible
+            });
+            if conflicts {
+                return Err(Status::INVALID_PARAMETER.into());
+            }
+            let (status, mapping) = map()?.split();
+            mappings.push(Tracked {
+                mapping: mapping.addr,
+                start,
+                end: start.saturating_add(mapping.size),
+                op,
+            });
+            Ok(Completion::new(status, mapping))
+        })
+        .unwrap_or_else(|| Err(Status::NOT_READY.into()))
+}
+
+/// Release a mapping with `unmap`, and forget about it if that succeeds.
+///
+/// Returns `NOT_READY` without calling `unmap` if the list of mappings is
+/// in use by preempted code.
+pub(super) fn untrack(mapping: *const c_void, unmap: impl FnOnce() -> Status) -> Status {
+    MAPPINGS
+        .with(|mappings| {
+            let status = unmap();
+            if !status.is_error() {
+                mappings.retain(|tracked| tracked.mapping != mapping);
+            }
+            status
+        })
+        .unwrap_or(Status::NOT_READY)
+}
@@ -0,0 +1,76 @@
+//! Vendor-specific capabilities.
+
+use super::{CapabilityId, PciConfigSpace, PciIO};
+use crate::{Result, ResultExt, Status};
+
+/// Offset of the length byte in a vendor-specific capability
+const VENDOR_CAP_LENGTH: u32 = 2;
+/// Offset of the vendor-defined data in a vendor-specific capability
+const VENDOR_CAP_DATA: u32 = 3;
+
+impl PciIO {
+    /// Iterate over the vendor-specific capabilities of the controller.
+    ///
+    /// Capabilities whose length byte cannot be read are skipped, and the
+    /// warnings encountered along the way are logged, as for
+    /// `PciConfigSpace::capabilities`.
+    pub fn vendor_capabilities(&self) -> Result<impl Iterator<Item = VendorCap<'_>> + '_> {
+        let config = self.config();
+        config.capabilities()
+            .map_inner(move |caps| {
+                caps.filter(|cap| cap.id == CapabilityId::VENDOR_SPECIFIC)
+                    .filter_map(move |cap| {
+                        let length = config.read_single::<u8>(cap.offset + VENDOR_CAP_LENGTH).ok()?.log();
+                        Some(VendorCap {
+                            config,
+                            offset: cap.offset,
+                            length
+                        })
+                    })
+            })
+    }
+}
+
+/// Vendor-specific capability, see `PciIO::vendor_capabilities`.
+///
+/// The layout of the capability is defined by the vendor, after the
+/// capability ID, the pointer to the next capability and the length byte.
+#[derive(Clone, Copy)]
+pub struct VendorCap<'a> {
+    config: PciConfigSpace<'a>,
+    offset: u32,
+    length: u8,
+}
+
+impl<'a> VendorCap<'a> {
+    /// Offset of the capability structure in configuration space.
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    /// Length of the capability structure in bytes, including its header,
+    /// as given by its length byte.
+    pub fn length(&self) -> u8 {
+        self.length
+    }
+
+    /// Read the vendor-defined data which follows the length byte.
+    ///
+    /// Fails with `BAD_BUFFER_SIZE` if the buffer is larger than the data.
+    pub fn read_bytes(&self, out: &mut [u8]) -> Result {
+        let available = usize::from(self.length).saturating_sub(VENDOR_CAP_DATA as usize);
+        if out.len() > available {
+            return Err(Status::BAD_BUFFER_SIZE.into());
+        }
+        self.config.read(self.offset + VENDOR_CAP_DATA, out)
+    }
+}
+
+impl core::fmt::Debug for VendorCap<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_struct("VendorCap")
+            .field("offset", &self.offset)
+            .field("length", &self.length)
+            .finish()
+    }
+}
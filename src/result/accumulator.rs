@@ -0,0 +1,58 @@
+use super::{Completion, Status};
+use alloc_api::vec::Vec;
+
+/// Collects the warnings of a sequence of UEFI operations
+///
+/// Unlike `Completion::with_status`, which can only store one warning and
+/// spills the others into the logs, this keeps a record of every distinct
+/// warning that was encountered so they can all be reported at the end.
+#[derive(Debug, Default)]
+pub struct WarningAccumulator {
+    warnings: Vec<Status>,
+}
+
+impl WarningAccumulator {
+    /// Create an accumulator which did not record any warning yet
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the warning of a completion, if any, and return its result
+    pub fn push<T>(&mut self, completion: Completion<T>) -> T {
+        let (status, result) = completion.split();
+        if !status.is_success() && !self.warnings.contains(&status) {
+            self.warnings.push(status);
+        }
+        result
+    }
+
+    /// Warnings recorded so far, in the order they were first encountered
+    pub fn warnings(&self) -> &[Status] {
+        &self.warnings
+    }
+
+    /// Consume the accumulator, returning the recorded warnings
+    pub fn finish(self) -> Vec<Status> {
+        self.warnings
+    }
+}
+
+/// Split the output of a batch of UEFI operations into their results and
+/// the warnings they reported
+///
+/// Every result is kept, in order. The warnings are returned together with
+/// the index of the completion which carried them.
+pub fn partition_warnings<T>(
+    completions: impl IntoIterator<Item = Completion<T>>,
+) -> (Vec<T>, Vec<(usize, Status)>) {
+    let mut results = Vec::new();
+    let mut warnings = Vec::new();
+    for (index, completion) in completions.into_iter().enumerate() {
+        let (status, result) = completion.split();
+        if !status.is_success() {
+            warnings.push((index, status));
+        }
+        results.push(result);
+    }
+    (results, warnings)
+}
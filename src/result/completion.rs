@@ -1,5 +1,9 @@
-use super::Status;
-use log::warn;
+use super::{Error, Result, Status};
+use core::cmp::Ordering;
+use log::{log, Level};
+
+#[cfg(feature = "exts")]
+use alloc_api::vec::Vec;
 
 /// This type is used when an UEFI operation has completed, but some non-fatal
 /// problems (UEFI warnings) may have been encountered along the way
@@ -12,6 +16,7 @@ pub struct Completion<T> {
 
 impl<T> Completion<T> {
     /// Build a completion from a non-error status and a function result
+    #[cfg_attr(feature = "track-caller", track_caller)]
     pub fn new(status: Status, result: T) -> Self {
         if status.is_error() {
             built_with_error(status);
@@ -35,6 +40,7 @@ impl<T> Completion<T> {
     }
 
     /// Access the inner value, logging the warning if there is any
+    #[cfg_attr(feature = "track-caller", track_caller)]
     pub fn log(self) -> T {
         if self.status != Status::SUCCESS {
             log_warning(self.status);
@@ -42,7 +48,21 @@ impl<T> Completion<T> {
         self.result
     }
 
+    /// Access the inner value, logging the warning if there is any at the
+    /// given level
+    ///
+    /// This is `log`, for callers which consider some warnings merely
+    /// informational and do not want them in the `warn` stream.
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub fn log_at(self, level: Level) -> T {
+        if self.status != Status::SUCCESS {
+            log_warning_at(level, self.status);
+        }
+        self.result
+    }
+
     /// Assume that no warning occured, panic if not
+    #[cfg_attr(feature = "track-caller", track_caller)]
     pub fn unwrap(self) -> T {
         if self.status != Status::SUCCESS {
             unwrap_failed(
@@ -54,6 +74,7 @@ impl<T> Completion<T> {
     }
 
     /// Assume that no warning occured, panic with provided message if not
+    #[cfg_attr(feature = "track-caller", track_caller)]
     pub fn expect(self, msg: &str) -> T {
         if self.status != Status::SUCCESS {
             unwrap_failed(msg, self.status);
@@ -61,6 +82,45 @@ impl<T> Completion<T> {
         self.result
     }
 
+    /// Check whether the inner value is equal to `value`, whatever the
+    /// warning status
+    pub fn contains<U>(&self, value: &U) -> bool
+    where
+        U: PartialEq<T>,
+    {
+        *value == self.result
+    }
+
+    /// Check whether this completion carries the given status
+    pub fn contains_status(&self, status: Status) -> bool {
+        self.status == status
+    }
+
+    /// Short human-readable description of how the operation completed
+    ///
+    /// This is `"ok"` for a clean success, and the description of the
+    /// warning otherwise.
+    pub fn describe(&self) -> &'static str {
+        if self.status.is_success() {
+            "ok"
+        } else {
+            self.status.description()
+        }
+    }
+
+    /// Treat the listed warnings as errors
+    ///
+    /// If this completion carries one of the given warnings, it is turned
+    /// into an error with that status and the inner value is dropped.
+    /// Other completions are returned as they are.
+    pub fn escalate_if(self, statuses: &[Status]) -> Result<T> {
+        if !self.status.is_success() && statuses.contains(&self.status) {
+            Err(Error::new(self.status, ()))
+        } else {
+            Ok(self)
+        }
+    }
+
     /// Transform the inner value without unwrapping the Completion
     pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Completion<U> {
         Completion {
@@ -73,6 +133,7 @@ impl<T> Completion<T> {
     ///
     /// Since this type only has storage for one warning, if two warnings must
     /// be stored, one of them will be spilled into the logs.
+    #[cfg_attr(feature = "track-caller", track_caller)]
     pub fn with_status(self, extra_status: Status) -> Self {
         if extra_status.is_success() {
             self
@@ -80,6 +141,143 @@ impl<T> Completion<T> {
             Completion::new(extra_status, self.log())
         }
     }
+
+    /// Discard the inner value, keeping the completion of a following
+    /// operation whose status gets merged with this one
+    ///
+    /// The warnings are merged like in `with_status`: if both operations
+    /// reported one, the warning of `next` is kept and the other is spilled
+    /// into the logs.
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub fn and<U>(self, next: Completion<U>) -> Completion<U> {
+        let (status, result) = next.split();
+        Completion::new(self.status, result).with_status(status)
+    }
+
+    /// Recover from a warning by computing a replacement completion
+    ///
+    /// If this completion carries a warning, `f` is called with it and its
+    /// completion is returned instead, for example the one of a retry with
+    /// different parameters. A clean success is returned as it is.
+    pub fn or_else(self, f: impl FnOnce(Status) -> Completion<T>) -> Completion<T> {
+        if self.status.is_success() {
+            self
+        } else {
+            f(self.status)
+        }
+    }
+
+    /// Apply a success or warning status, unless this completion already
+    /// carries a warning
+    ///
+    /// Unlike `with_status`, the existing warning takes precedence, so
+    /// nothing is ever spilled into the logs.
+    pub fn status_or(self, status: Status) -> Self {
+        if self.status.is_success() {
+            Completion::new(status, self.result)
+        } else {
+            self
+        }
+    }
+}
+
+#[cfg(feature = "test-utils")]
+impl<T: PartialEq + core::fmt::Debug> Completion<T> {
+    /// Assert that this completion is a clean success carrying `expected`
+    ///
+    /// Unlike comparing the output of `ignore_warning`, this also catches
+    /// unexpected warnings. The panic message reports both the status and
+    /// the value of the completion.
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub fn assert_eq_value(self, expected: T) {
+        if self.status != Status::SUCCESS || self.result != expected {
+            panic!(
+                "Completion mismatch: expected value {:?} with status {:?}, got value {:?} with status {:?}",
+                expected,
+                Status::SUCCESS,
+                self.result,
+                self.status
+            );
+        }
+    }
+}
+
+#[cfg(feature = "exts")]
+impl<T> Completion<Vec<T>> {
+    /// Fold a sequence of completions into a single one, keeping the most
+    /// severe warning according to `Status::cmp_severity`
+    ///
+    /// Every result is kept, in order. The other warnings are spilled into
+    /// the logs, as with `with_status`.
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub fn reduce(completions: impl IntoIterator<Item = Completion<T>>) -> Self {
+        let mut status = Status::SUCCESS;
+        let mut results = Vec::new();
+        for completion in completions {
+            let (warning, result) = completion.split();
+            status = merge_worst(status, warning);
+            results.push(result);
+        }
+        Completion::new(status, results)
+    }
+}
+
+impl Completion<bool> {
+    /// Check whether every completion carries `true`, stopping at the
+    /// first `false`
+    ///
+    /// The most severe warning among the completions which were checked is
+    /// kept, and the others are spilled into the logs, as with `reduce`.
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub fn all(completions: impl IntoIterator<Item = Completion<bool>>) -> Self {
+        Self::fold_until(completions, false)
+    }
+
+    /// Check whether any completion carries `true`, stopping at the first
+    /// `true`
+    ///
+    /// Warnings are merged like in `all`.
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub fn any(completions: impl IntoIterator<Item = Completion<bool>>) -> Self {
+        Self::fold_until(completions, true)
+    }
+
+    /// Merge completions until one carries `stop`, which is then the result
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    fn fold_until(completions: impl IntoIterator<Item = Completion<bool>>, stop: bool) -> Self {
+        let mut status = Status::SUCCESS;
+        for completion in completions {
+            let (warning, result) = completion.split();
+            status = merge_worst(status, warning);
+            if result == stop {
+                return Completion::new(status, stop);
+            }
+        }
+        Completion::new(status, !stop)
+    }
+}
+
+impl<A, B, C> Completion<(A, B, C)> {
+    /// Join three completions into one holding all of their results
+    ///
+    /// The most severe warning according to `Status::cmp_severity` is kept,
+    /// and the other warnings are spilled into the logs, as with `reduce`.
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub fn join3(a: Completion<A>, b: Completion<B>, c: Completion<C>) -> Self {
+        let status = merge_worst(merge_worst(a.status, b.status), c.status);
+        Completion::new(status, (a.result, b.result, c.result))
+    }
+}
+
+impl<A, B, C, D> Completion<(A, B, C, D)> {
+    /// Join four completions into one holding all of their results
+    ///
+    /// Warnings are merged like in `join3`.
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub fn join4(a: Completion<A>, b: Completion<B>, c: Completion<C>, d: Completion<D>) -> Self {
+        let status = merge_worst(merge_worst(merge_worst(a.status, b.status), c.status), d.status);
+        Completion::new(status, (a.result, b.result, c.result, d.result))
+    }
 }
 
 // Completions can be built from either a status or a payload
@@ -96,10 +294,19 @@ impl<T> From<T> for Completion<T> {
     }
 }
 
+// A default completion is a successful one
+
+impl<T: Default> Default for Completion<T> {
+    fn default() -> Self {
+        Completion::new(Status::SUCCESS, T::default())
+    }
+}
+
 // These are separate functions to reduce the code size of the methods
 
 #[inline(never)]
 #[cold]
+#[cfg_attr(feature = "track-caller", track_caller)]
 fn built_with_error(error: Status) -> ! {
     panic!(
         "Completion was incorrectly built with error status: {:?}",
@@ -109,12 +316,82 @@ fn built_with_error(error: Status) -> ! {
 
 #[inline(never)]
 #[cold]
+#[cfg_attr(feature = "track-caller", track_caller)]
 fn unwrap_failed(msg: &str, warning: Status) -> ! {
     panic!("{}: {:?}", msg, warning)
 }
 
+/// Keep the most severe of two statuses according to `Status::cmp_severity`,
+/// spilling the other one into the logs if it is a warning
+#[cfg_attr(feature = "track-caller", track_caller)]
+fn merge_worst(status: Status, warning: Status) -> Status {
+    if warning.cmp_severity(status) == Ordering::Greater {
+        if !status.is_success() {
+            log_warning(status);
+        }
+        warning
+    } else {
+        if !warning.is_success() {
+            log_warning(warning);
+        }
+        status
+    }
+}
+
 #[inline(never)]
 #[cold]
+#[cfg_attr(feature = "track-caller", track_caller)]
 fn log_warning(warning: Status) {
-    warn!("Encountered UEFI warning: {:?}", warning)
+    log_warning_at(Level::Warn, warning)
+}
+
+#[inline(never)]
+#[cold]
+#[cfg(not(feature = "track-caller"))]
+fn log_warning_at(level: Level, warning: Status) {
+    log!(level, "Encountered UEFI warning: {:?}", warning)
+}
+
+#[inline(never)]
+#[cold]
+#[cfg(feature = "track-caller")]
+#[track_caller]
+fn log_warning_at(level: Level, warning: Status) {
+    log!(level, "at {}: Encountered UEFI warning: {:?}", core::panic::Location::caller(), warning)
+}
+
+/// Unwrap the output of an UEFI operation, propagating errors and keeping
+/// track of warnings
+///
+/// Since a `Completion` never carries an error, the `?` operator has nothing
+/// to short-circuit on and would silently drop the warning of a successful
+/// operation. This macro instead returns early on errors, like `?` does, but
+/// merges the warning of a successful operation into a `Status` accumulator
+/// using the same rules as `Completion::with_status`. The enclosing function
+/// can then report the warnings it encountered in its own `Completion`:
+///
+/// ```
+/// fn read_ids(pci: &PciIO) -> uefi::Result<(u16, u16)> {
+///     let mut status = Status::SUCCESS;
+///     let vendor = try_completion!(status, pci.read_config_single::<u16>(0));
+///     let device = try_completion!(status, pci.read_config_single::<u16>(2));
+///     status.into_with_val(|| (vendor, device))
+/// }
+/// ```
+#[macro_export]
+macro_rules! try_completion {
+    ($status:ident, $result:expr) => {
+        match $result {
+            ::core::result::Result::Ok(completion) => {
+                let (warning, value) = $crate::Completion::split(completion);
+                $status = $crate::Completion::new($status, ())
+                    .with_status(warning)
+                    .status();
+                value
+            }
+            ::core::result::Result::Err(error) => {
+                return ::core::result::Result::Err(::core::convert::From::from(error));
+            }
+        }
+    };
 }
@@ -17,6 +17,12 @@ use core::fmt::Debug;
 mod completion;
 pub use self::completion::Completion;
 
+/// Collection of the warnings encountered during a sequence of operations
+#[cfg(feature = "exts")]
+mod accumulator;
+#[cfg(feature = "exts")]
+pub use self::accumulator::{partition_warnings, WarningAccumulator};
+
 /// The error type that we use, essentially a status code + optional additional data
 mod error;
 pub use self::error::Error;
@@ -69,14 +75,21 @@ impl<Output, ErrData: Debug> ResultExt<Output, ErrData> for Result<Output, ErrDa
         }
     }
 
+    #[cfg_attr(feature = "track-caller", track_caller)]
     fn log_warning(self) -> core::result::Result<Output, Error<ErrData>> {
-        self.map(Completion::log)
+        // Not `map`, which would hide the caller of this method
+        match self {
+            Ok(completion) => Ok(completion.log()),
+            Err(error) => Err(error),
+        }
     }
 
+    #[cfg_attr(feature = "track-caller", track_caller)]
     fn unwrap_success(self) -> Output {
         self.unwrap().unwrap()
     }
 
+    #[cfg_attr(feature = "track-caller", track_caller)]
     fn expect_success(self, msg: &str) -> Output {
         self.expect(msg).expect(msg)
     }
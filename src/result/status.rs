@@ -3,11 +3,17 @@ use core::{
     convert::Infallible,
     ops::{ControlFlow, FromResidual, Try},
 };
-use core::{fmt::Debug, num::NonZeroUsize};
+use core::{cmp::Ordering, fmt::Debug, num::NonZeroUsize};
 
 /// Bit indicating that an UEFI status code is an error
 const ERROR_BIT: usize = 1 << (core::mem::size_of::<usize>() * 8 - 1);
 
+/// Bit indicating that an UEFI status code is reserved for OEMs
+const OEM_BIT: usize = ERROR_BIT >> 1;
+
+/// Bit indicating that an UEFI status code is reserved for the PI specification
+const PI_BIT: usize = ERROR_BIT >> 2;
+
 newtype_enum! {
 /// UEFI uses status codes in order to report successes, errors, and warnings.
 ///
@@ -126,7 +132,157 @@ impl Status {
         self.0 & ERROR_BIT != 0
     }
 
+    /// Raw value of this status code, as an `EFI_STATUS` expected by C code.
+    ///
+    /// The error bit is the highest bit of a `usize`, so the value depends
+    /// on the width of the target and must not be truncated.
+    #[inline]
+    pub fn as_usize(self) -> usize {
+        self.0
+    }
+
+    /// Build a status code from a raw `EFI_STATUS` value received from C
+    /// code, whether or not it is one of the codes defined in this crate.
+    #[inline]
+    pub const fn from_usize(value: usize) -> Status {
+        Status(value)
+    }
+
+    /// Returns true if the status code lies in the range reserved for OEMs.
+    ///
+    /// Such warnings and errors are vendor-specific and have no meaning
+    /// outside of the firmware that returned them.
+    #[inline]
+    pub fn is_oem_defined(self) -> bool {
+        self.0 & OEM_BIT != 0
+    }
+
+    /// Returns true if the status code lies in the range reserved for the
+    /// Platform Initialization specification.
+    #[inline]
+    pub fn is_pi_defined(self) -> bool {
+        !self.is_oem_defined() && self.0 & PI_BIT != 0
+    }
+
+    /// Returns true if the status code lies in a range reserved for the
+    /// UEFI or PI specifications, but is not one of the codes defined in
+    /// this crate.
+    ///
+    /// Such codes are either defined by a newer specification revision or
+    /// returned by a non-compliant implementation.
+    pub fn is_reserved(self) -> bool {
+        !self.is_oem_defined() && self.known_description().is_none()
+    }
+
+    /// Raw code of this status within its range, without the bits
+    /// identifying errors and OEM or PI defined codes.
+    #[inline]
+    pub fn code(self) -> usize {
+        self.0 & !(ERROR_BIT | OEM_BIT | PI_BIT)
+    }
+
+    /// Rank of this status code, higher values being more severe.
+    ///
+    /// Success ranks lowest and errors highest. Warnings are ranked by the
+    /// harm they suggest: purely informational ones first, then warnings
+    /// the crate does not know about, then truncated data, then data which
+    /// may have been lost, and finally a pending system reset.
+    ///
+    /// This is the ordering used by the combinators which keep the worst
+    /// of several warnings, such as `Completion::reduce`. Distinct codes may
+    /// share a rank; `cmp_severity` refines it into a total order.
+    pub fn severity(self) -> u8 {
+        match self {
+            Status::SUCCESS => 0,
+            _ if self.is_error() => 6,
+            Status::WARN_UNKNOWN_GLYPH
+            | Status::WARN_STALE_DATA
+            | Status::WARN_FILE_SYSTEM => 1,
+            Status::WARN_BUFFER_TOO_SMALL => 3,
+            Status::WARN_DELETE_FAILURE | Status::WARN_WRITE_FAILURE => 4,
+            Status::WARN_RESET_REQUIRED => 5,
+            _ => 2,
+        }
+    }
+
+    /// Compare the severity of two status codes.
+    ///
+    /// Codes are ordered by `severity`, then codes of the same rank by their
+    /// value, so that the outcome never depends on the order in which they
+    /// were encountered.
+    pub fn cmp_severity(self, other: Status) -> Ordering {
+        self.severity()
+            .cmp(&other.severity())
+            .then(self.0.cmp(&other.0))
+    }
+
+    /// Short human-readable description of this status code.
+    ///
+    /// Implementation-specific codes, which are not defined by the UEFI
+    /// specification, are described according to their class only.
+    pub fn description(self) -> &'static str {
+        match self.known_description() {
+            Some(description) => description,
+            None if self.is_error() => "unknown error",
+            None => "unknown warning",
+        }
+    }
+
+    /// Description of the status codes defined in this crate
+    fn known_description(self) -> Option<&'static str> {
+        let description = match self {
+            Status::SUCCESS => "the operation completed successfully",
+            Status::WARN_UNKNOWN_GLYPH => "some characters could not be rendered and were skipped",
+            Status::WARN_DELETE_FAILURE => "the handle was closed, but the file was not deleted",
+            Status::WARN_WRITE_FAILURE => "the handle was closed, but the data was not flushed",
+            Status::WARN_BUFFER_TOO_SMALL => "the buffer was too small and the data was truncated",
+            Status::WARN_STALE_DATA => "the data has not been updated within the local policy timeframe",
+            Status::WARN_FILE_SYSTEM => "the buffer contains an UEFI-compliant file system",
+            Status::WARN_RESET_REQUIRED => "the operation will be processed across a system reset",
+            Status::LOAD_ERROR => "the image failed to load",
+            Status::INVALID_PARAMETER => "a parameter was incorrect",
+            Status::UNSUPPORTED => "the operation is not supported",
+            Status::BAD_BUFFER_SIZE => "the buffer was not the proper size for the request",
+            Status::BUFFER_TOO_SMALL => "the buffer is not large enough to hold the requested data",
+            Status::NOT_READY => "there is no data pending",
+            Status::DEVICE_ERROR => "the physical device reported an error",
+            Status::WRITE_PROTECTED => "the device cannot be written to",
+            Status::OUT_OF_RESOURCES => "a resource has run out",
+            Status::VOLUME_CORRUPTED => "an inconsistency was detected on the file system",
+            Status::VOLUME_FULL => "there is no more space on the file system",
+            Status::NO_MEDIA => "the device does not contain any medium",
+            Status::MEDIA_CHANGED => "the medium in the device has changed",
+            Status::NOT_FOUND => "the item was not found",
+            Status::ACCESS_DENIED => "access was denied",
+            Status::NO_RESPONSE => "the server was not found or did not respond",
+            Status::NO_MAPPING => "a mapping to a device does not exist",
+            Status::TIMEOUT => "the timeout time expired",
+            Status::NOT_STARTED => "the protocol has not been started",
+            Status::ALREADY_STARTED => "the protocol has already been started",
+            Status::ABORTED => "the operation was aborted",
+            Status::ICMP_ERROR => "an ICMP error occurred during the network operation",
+            Status::TFTP_ERROR => "a TFTP error occurred during the network operation",
+            Status::PROTOCOL_ERROR => "a protocol error occurred during the network operation",
+            Status::INCOMPATIBLE_VERSION => "an incompatible internal version was encountered",
+            Status::SECURITY_VIOLATION => "the operation was not performed due to a security violation",
+            Status::CRC_ERROR => "a CRC error was detected",
+            Status::END_OF_MEDIA => "beginning or end of media was reached",
+            Status::END_OF_FILE => "the end of the file was reached",
+            Status::INVALID_LANGUAGE => "the language specified was invalid",
+            Status::COMPROMISED_DATA => "the security status of the data is unknown or compromised",
+            Status::IP_ADDRESS_CONFLICT => "there is an address conflict in address allocation",
+            Status::HTTP_ERROR => "an HTTP error occurred during the network operation",
+            _ => return None,
+        };
+        Some(description)
+    }
+
     /// Converts this status code into a result with a given value.
+    ///
+    /// This is how protocol wrappers turn the status returned by the
+    /// firmware into a `Result`. A warning status is not discarded: it is
+    /// kept in the `Completion` next to the value, and `val` is only called
+    /// if the status is not an error.
     #[inline]
     #[allow(clippy::result_unit_err)]
     pub fn into_with_val<T>(self, val: impl FnOnce() -> T) -> Result<T, ()> {
@@ -137,7 +293,42 @@ impl Status {
         }
     }
 
+    /// Converts this status code into a result with a given value, keeping
+    /// a warning status in the returned `Completion`.
+    ///
+    /// This is `into_with_val`, which never discards warnings, under a name
+    /// which says so at the call site.
+    #[inline]
+    #[allow(clippy::result_unit_err)]
+    pub fn into_with_val_and_warn<T>(self, val: impl FnOnce() -> T) -> Result<T, ()> {
+        self.into_with_val(val)
+    }
+
+    /// Converts this status code into a result, for use with `?`.
+    ///
+    /// This is the same conversion as `Status::into`, spelled so that it is
+    /// easy to find and does not need a type annotation. A warning status is
+    /// kept in the `Completion`.
+    #[inline]
+    #[allow(clippy::result_unit_err)]
+    pub fn ok(self) -> Result {
+        self.into()
+    }
+
+    /// Converts this status code into a result with a given value, for use
+    /// with `?`.
+    ///
+    /// This is `into_with_val` under a name matching `ok`.
+    #[inline]
+    #[allow(clippy::result_unit_err)]
+    pub fn ok_or_val<T>(self, val: impl FnOnce() -> T) -> Result<T> {
+        self.into_with_val(val)
+    }
+
     /// Converts this status code into a result with a given error payload
+    ///
+    /// As with `into_with_val`, a warning status is kept in the resulting
+    /// `Completion`. `err` is only called if the status is an error.
     #[inline]
     pub fn into_with_err<ErrData: Debug>(
         self,
@@ -151,6 +342,9 @@ impl Status {
     }
 
     /// Convert this status code into a result with a given value and error payload
+    ///
+    /// This combines `into_with_val` and `into_with_err`, only one of the
+    /// two closures is called depending on the status.
     #[inline]
     pub fn into_with<T, ErrData: Debug>(
         self,
@@ -165,7 +359,8 @@ impl Status {
     }
 }
 
-// An UEFI status is equivalent to a Result with no data or error payload
+// An UEFI status is equivalent to a Result with no data or error payload,
+// warnings being kept in the Completion
 impl From<Status> for Result<(), ()> {
     #[inline]
     fn from(status: Status) -> Result<(), ()> {
@@ -173,6 +368,34 @@ impl From<Status> for Result<(), ()> {
     }
 }
 
+/// Call a firmware function and convert the status it returns
+///
+/// This is the usual way for protocol wrappers to call into the firmware.
+/// The status is converted into a `Result` like `Status::into` does, keeping
+/// warnings in the `Completion`. If an expression follows the call, it is
+/// evaluated when the status is not an error and becomes the value of the
+/// completion, which is handy to return an out-parameter:
+///
+/// ```
+/// pub fn flush(&self) -> uefi::Result {
+///     efi_call!((self.flush)(self))
+/// }
+///
+/// pub fn allocate(&self, pages: usize) -> uefi::Result<u64> {
+///     let mut host_addr = 0;
+///     efi_call!((self.allocate)(self, pages, &mut host_addr) => host_addr)
+/// }
+/// ```
+#[macro_export]
+macro_rules! efi_call {
+    ($call:expr) => {
+        <$crate::Result as ::core::convert::From<$crate::Status>>::from($call)
+    };
+    ($call:expr => $out:expr) => {
+        $crate::Status::into_with_val($call, || $out)
+    };
+}
+
 pub struct StatusResidual(NonZeroUsize);
 
 impl Try for Status {
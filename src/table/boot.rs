@@ -117,7 +117,8 @@ pub struct BootServices {
         extern "efiapi" fn(handle: Handle, proto: &Guid, out: &mut *mut c_void, agent: Handle, controller: Handle, attributes: OpenAttribute) -> Status,
     close_protocol:
         extern "efiapi" fn(handle: Handle, proto: &Guid, agent: Handle, controller: Handle) -> Status,
-    open_protocol_information: usize,
+    open_protocol_information:
+        extern "efiapi" fn(handle: Handle, proto: &Guid, entry_buffer: &mut *mut OpenProtocolInformationEntry, entry_count: &mut usize) -> Status,
 
     // Library services
     protocols_per_handle: usize,
@@ -226,6 +227,17 @@ impl BootServices {
         })
     }
 
+    /// Open a protocol interface for use by the `agent` image, closing it
+    /// when the returned guard is dropped.
+    ///
+    /// The protocol is identified by its GUID and opened with the
+    /// `GET_PROTOCOL` attribute, which is suitable for consumers of a
+    /// protocol. Drivers managing a controller should use `open_protocol`
+    /// with `BY_DRIVER` instead, to get notified when it is disconnected.
+    pub fn open<P: Protocol>(&self, handle: Handle, agent: Handle) -> Result<ScopedProtocol<P>> {
+        self.open_protocol(handle, agent, Handle::null(), OpenAttribute::GET_PROTOCOL)
+    }
+
     pub fn close_protocol<P: Protocol>(&self, handle: Handle, agent: Handle, controller: Handle) -> Result {
         (self.close_protocol)(handle, &P::GUID, agent, controller)
             .into()
@@ -682,6 +694,24 @@ impl BootServices {
             .map(|completion| completion.with_status(status2))
     }
 
+    /// Retrieve the list of agents which have the protocol `P` of a handle
+    /// opened, along with the attributes they opened it with.
+    pub fn open_protocol_information<P: Protocol>(&self, handle: Handle) -> Result<Vec<OpenProtocolInformationEntry>> {
+        let mut entry_buffer = ptr::null_mut();
+        let mut entry_count = 0;
+        let mut status = (self.open_protocol_information)(handle, &P::GUID, &mut entry_buffer, &mut entry_count);
+        if status.is_error() {
+            return Err(status.into());
+        }
+        let mut entries = Vec::with_capacity(entry_count);
+        if !entry_buffer.is_null() {
+            entries.extend_from_slice(unsafe { core::slice::from_raw_parts(entry_buffer, entry_count) });
+            // The buffer is allocated from pool by the firmware
+            crate::try_completion!(status, self.free_pool(entry_buffer.cast()));
+        }
+        status.into_with_val(|| entries)
+    }
+
     pub fn create_child<'boot, P: Protocol + 'boot>(&'boot self, proto: &'boot P) -> Result<Handle> {
         let mut out_handle = Handle::null();
         let proto = (proto as *const P).cast();
@@ -777,6 +807,7 @@ impl Drop for TplGuard<'_> {
     }
 }
 
+/// Guard over an opened protocol interface, closing it on drop.
 pub struct OpenProtocol<'boot, P: Protocol + 'boot> {
     boot_services: &'boot BootServices,
     handle: Handle,
@@ -807,6 +838,9 @@ impl<'boot, P: Protocol + 'boot> OpenProtocol<'boot, P> {
     }
 }
 
+/// Protocol interface opened by `BootServices::open`.
+pub type ScopedProtocol<'boot, P> = OpenProtocol<'boot, P>;
+
 pub fn leak<'boot, P: Protocol + 'boot>(guard: OpenProtocol<'boot, P>) -> &'boot UnsafeCell<P> {
     let cell = guard.proto;
     mem::forget(guard);
@@ -848,6 +882,21 @@ bitflags! {
     }
 }
 
+/// Description of an agent which opened a protocol, see
+/// `BootServices::open_protocol_information`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct OpenProtocolInformationEntry {
+    /// Image or driver handle which opened the protocol
+    pub agent: Handle,
+    /// Controller the protocol was opened for, for drivers
+    pub controller: Handle,
+    /// Attributes the protocol was opened with
+    pub attributes: OpenAttribute,
+    /// Number of times the protocol was opened with these parameters
+    pub open_count: u32,
+}
+
 newtype_enum! {
 /// The type of a memory range.
 ///
@@ -5,7 +5,11 @@ extern crate proc_macro;
 use proc_macro::TokenStream;
 use quote::{quote, TokenStreamExt};
 use syn::parse::{Parse, ParseStream};
-use syn::{parse_macro_input, DeriveInput, Generics, Ident, ItemFn, ItemType, LitStr};
+use syn::punctuated::Punctuated;
+use syn::{
+    braced, parse_macro_input, token, DeriveInput, Generics, Ident, ItemFn, ItemType, LitInt,
+    LitStr, Token,
+};
 
 /// Parses a type definition, extracts its identifier and generic parameters
 struct TypeDefinition {
@@ -31,57 +35,143 @@ impl Parse for TypeDefinition {
     }
 }
 
-/// `unsafe_guid` attribute macro, implements the `Identify` trait for any type
-/// (mostly works like a custom derive, but also supports type aliases)
-#[proc_macro_attribute]
-pub fn unsafe_guid(args: TokenStream, input: TokenStream) -> TokenStream {
-    // Parse the arguments and input using Syn
-    let guid_str = parse_macro_input!(args as LitStr).value();
-    let mut result: proc_macro2::TokenStream = input.clone().into();
-    let type_definition = parse_macro_input!(input as TypeDefinition);
+/// Parses the arguments of `unsafe_guid`, which are either a canonical GUID
+/// string or the fields of a C initializer, as found in EDK2 headers
+struct GuidArgs {
+    time_low: u32,
+    time_mid: u16,
+    time_high_and_version: u16,
+    clock_seq_and_variant: u16,
+    node: [u8; 6],
+}
 
-    // We expect a canonical GUID string, such as "12345678-9abc-def0-fedc-ba9876543210"
-    if guid_str.len() != 36 {
-        panic!(
-            "\"{}\" is not a canonical GUID string (expected 36 bytes, found {})",
-            guid_str,
-            guid_str.len()
-        );
+impl Parse for GuidArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(LitStr) {
+            let guid_str = input.parse::<LitStr>()?.value();
+            Ok(Self::from_str(&guid_str))
+        } else if input.peek(token::Brace) {
+            let content;
+            braced!(content in input);
+            Self::from_fields(&content)
+        } else {
+            Self::from_fields(input)
+        }
     }
-    let mut guid_hex_iter = guid_str.split('-');
-    let mut next_guid_int = |expected_num_bits: usize| -> u64 {
-        let guid_hex_component = guid_hex_iter.next().unwrap();
-        if guid_hex_component.len() != expected_num_bits / 4 {
+}
+
+impl GuidArgs {
+    /// Parse a canonical GUID string, such as "12345678-9abc-def0-fedc-ba9876543210"
+    fn from_str(guid_str: &str) -> Self {
+        if guid_str.len() != 36 {
             panic!(
-                "GUID component \"{}\" is not a {}-bit hexadecimal string",
-                guid_hex_component, expected_num_bits
+                "\"{}\" is not a canonical GUID string (expected 36 bytes, found {})",
+                guid_str,
+                guid_str.len()
             );
         }
-        match u64::from_str_radix(guid_hex_component, 16) {
-            Ok(number) => number,
-            _ => panic!(
-                "GUID component \"{}\" is not a hexadecimal number",
-                guid_hex_component
-            ),
+        let mut guid_hex_iter = guid_str.split('-');
+        let mut next_guid_int = |expected_num_bits: usize| -> u64 {
+            let guid_hex_component = guid_hex_iter.next().unwrap();
+            if guid_hex_component.len() != expected_num_bits / 4 {
+                panic!(
+                    "GUID component \"{}\" is not a {}-bit hexadecimal string",
+                    guid_hex_component, expected_num_bits
+                );
+            }
+            match u64::from_str_radix(guid_hex_component, 16) {
+                Ok(number) => number,
+                _ => panic!(
+                    "GUID component \"{}\" is not a hexadecimal number",
+                    guid_hex_component
+                ),
+            }
+        };
+
+        // The GUID string is composed of a 32-bit integer, three 16-bit ones, and a 48-bit one
+        let time_low = next_guid_int(32) as u32;
+        let time_mid = next_guid_int(16) as u16;
+        let time_high_and_version = next_guid_int(16) as u16;
+        let clock_seq_and_variant = next_guid_int(16) as u16;
+        let node_64 = next_guid_int(48);
+
+        // Convert the node ID to an array of bytes to comply with Guid::from_values expectations
+        let node = [
+            (node_64 >> 40) as u8,
+            ((node_64 >> 32) % 0x100) as u8,
+            ((node_64 >> 24) % 0x100) as u8,
+            ((node_64 >> 16) % 0x100) as u8,
+            ((node_64 >> 8) % 0x100) as u8,
+            (node_64 % 0x100) as u8,
+        ];
+
+        Self {
+            time_low,
+            time_mid,
+            time_high_and_version,
+            clock_seq_and_variant,
+            node,
         }
-    };
+    }
+
+    /// Parse the fields of a C initializer, such as
+    /// `0x12345678, 0x9abc, 0xdef0, { 0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10 }`
+    fn from_fields(input: ParseStream) -> syn::Result<Self> {
+        let time_low = input.parse::<LitInt>()?.base10_parse::<u32>()?;
+        input.parse::<Token![,]>()?;
+        let time_mid = input.parse::<LitInt>()?.base10_parse::<u16>()?;
+        input.parse::<Token![,]>()?;
+        let time_high_and_version = input.parse::<LitInt>()?.base10_parse::<u16>()?;
+        input.parse::<Token![,]>()?;
+
+        // The last eight bytes are given one by one
+        let content;
+        let brace = braced!(content in input);
+        let bytes = Punctuated::<LitInt, Token![,]>::parse_terminated(&content)?
+            .iter()
+            .map(|byte| byte.base10_parse::<u8>())
+            .collect::<syn::Result<Vec<u8>>>()?;
+        if bytes.len() != 8 {
+            return Err(syn::Error::new(
+                brace.span,
+                format!("expected 8 GUID bytes, found {}", bytes.len()),
+            ));
+        }
+        if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+        }
+
+        let mut node = [0; 6];
+        node.copy_from_slice(&bytes[2..]);
+        Ok(Self {
+            time_low,
+            time_mid,
+            time_high_and_version,
+            clock_seq_and_variant: u16::from_be_bytes([bytes[0], bytes[1]]),
+            node,
+        })
+    }
+}
 
-    // The GUID string is composed of a 32-bit integer, three 16-bit ones, and a 48-bit one
-    let time_low = next_guid_int(32) as u32;
-    let time_mid = next_guid_int(16) as u16;
-    let time_high_and_version = next_guid_int(16) as u16;
-    let clock_seq_and_variant = next_guid_int(16) as u16;
-    let node_64 = next_guid_int(48);
-
-    // Convert the node ID to an array of bytes to comply with Guid::from_values expectations
-    let node = [
-        (node_64 >> 40) as u8,
-        ((node_64 >> 32) % 0x100) as u8,
-        ((node_64 >> 24) % 0x100) as u8,
-        ((node_64 >> 16) % 0x100) as u8,
-        ((node_64 >> 8) % 0x100) as u8,
-        (node_64 % 0x100) as u8,
-    ];
+/// `unsafe_guid` attribute macro, implements the `Identify` trait for any type
+/// (mostly works like a custom derive, but also supports type aliases)
+///
+/// The GUID is given either as a canonical string, such as
+/// `"12345678-9abc-def0-fedc-ba9876543210"`, or with the fields of a C
+/// initializer, optionally surrounded by braces, such as
+/// `0x12345678, 0x9abc, 0xdef0, { 0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10 }`.
+#[proc_macro_attribute]
+pub fn unsafe_guid(args: TokenStream, input: TokenStream) -> TokenStream {
+    // Parse the arguments and input using Syn
+    let GuidArgs {
+        time_low,
+        time_mid,
+        time_high_and_version,
+        clock_seq_and_variant,
+        node,
+    } = parse_macro_input!(args as GuidArgs);
+    let mut result: proc_macro2::TokenStream = input.clone().into();
+    let type_definition = parse_macro_input!(input as TypeDefinition);
 
     // At this point, we know everything we need to implement Identify
     let ident = type_definition.ident.clone();
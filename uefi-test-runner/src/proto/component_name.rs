@@ -0,0 +1,25 @@
+use core::ptr::{self, NonNull};
+use uefi::data_types::{CStr8, Char16, Char8};
+use uefi::proto::component_name::ComponentName2;
+use uefi::{Handle, Status};
+
+pub fn test() {
+    info!("Running component name null driver name test");
+
+    let languages = b"en\0";
+    let name = ComponentName2::new(get_driver_name, get_controller_name, languages.as_ptr() as *const Char8);
+    let language = unsafe { CStr8::from_bytes_with_nul_unchecked(languages) };
+
+    // Firmware reporting success without a name must not yield a string
+    let status = name.driver_name(language).map(|_| ()).unwrap_err().status();
+    assert_eq!(status, Status::NOT_FOUND);
+}
+
+extern "efiapi" fn get_driver_name(_: &ComponentName2, _: *const Char8, driver_name: *mut *const Char16) -> Status {
+    unsafe { *driver_name = ptr::null() };
+    Status::SUCCESS
+}
+
+extern "efiapi" fn get_controller_name(_: &ComponentName2, _: Handle, _: Option<NonNull<Handle>>, _: *const Char8, _: *mut *const Char16) -> Status {
+    Status::UNSUPPORTED
+}
@@ -0,0 +1,49 @@
+use core::ptr;
+use uefi::proto::device_path::DevicePath;
+use uefi::proto::driver_binding::DriverBinding;
+use uefi::table::boot::BootServices;
+use uefi::{Handle, ResultExt, Status};
+
+pub fn test(bt: &BootServices) {
+    info!("Running driver binding stop dispatch test");
+
+    let handles = bt
+        .find_handles::<DriverBinding>()
+        .expect_success("Failed to retrieve list of driver binding handles");
+    let handle = match handles.first() {
+        Some(&handle) => handle,
+        None => {
+            info!("No driver binding is installed");
+            return;
+        }
+    };
+
+    let binding = DriverBinding::new(start, supported, stop, 0, handle, handle);
+
+    // The firmware passes a null child list when stopping the bus itself
+    assert_eq!(stop(&binding, handle, 0, ptr::null_mut()), Status::SUCCESS);
+
+    let mut children = [handle];
+    assert_eq!(
+        stop(&binding, handle, children.len(), children.as_mut_ptr()),
+        Status::ABORTED
+    );
+}
+
+extern "efiapi" fn supported(_: &DriverBinding, _: Handle, _: *mut DevicePath) -> Status {
+    Status::UNSUPPORTED
+}
+
+extern "efiapi" fn start(_: &DriverBinding, _: Handle, _: *mut DevicePath) -> Status {
+    Status::UNSUPPORTED
+}
+
+extern "efiapi" fn stop(_: &DriverBinding, _: Handle, num_child_controller: usize, child_controller: *mut Handle) -> Status {
+    let children = unsafe { DriverBinding::child_controllers(num_child_controller, child_controller) };
+    if children.is_empty() {
+        Status::SUCCESS
+    } else {
+        assert_eq!(children.len(), num_child_controller);
+        Status::ABORTED
+    }
+}
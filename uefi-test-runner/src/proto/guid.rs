@@ -0,0 +1,101 @@
+use alloc::format;
+use uefi::proto;
+use uefi::{Guid, Identify};
+
+/// GUIDs of every protocol defined by the crate.
+///
+/// A protocol added to the crate should also be added here, so that a
+/// copy-pasted GUID is caught before `OpenProtocol` silently fails to find it.
+const PROTOCOL_GUIDS: &[(&str, Guid)] = &[
+    ("AcpiSdt", proto::acpi_sdt::AcpiSdt::GUID),
+    ("AcpiTable", proto::acpi_table::AcpiTable::GUID),
+    ("BlockIO", proto::media::block::BlockIO::GUID),
+    ("ComponentName", proto::component_name::ComponentName::GUID),
+    ("ComponentName2", proto::component_name::ComponentName2::GUID),
+    ("DebugSupport", proto::debug::DebugSupport::GUID),
+    ("DevicePath", proto::device_path::DevicePath::GUID),
+    ("DriverBinding", proto::driver_binding::DriverBinding::GUID),
+    ("DriverConfiguration2", proto::driver_configuration::DriverConfiguration2::GUID),
+    ("DriverDiagnostics2", proto::driver_diagnostics::DriverDiagnostics2::GUID),
+    ("GraphicsOutput", proto::console::gop::GraphicsOutput::<'static>::GUID),
+    ("Input", proto::console::text::Input::GUID),
+    ("LoadedImage", proto::loaded_image::LoadedImage::GUID),
+    ("MpServices", proto::pi::mp::MpServices::GUID),
+    ("Output", proto::console::text::Output::<'static>::GUID),
+    ("PartitionInfo", proto::media::partition::PartitionInfo::GUID),
+    ("PciIO", proto::pci::PciIO::GUID),
+    ("Pointer", proto::console::pointer::Pointer::<'static>::GUID),
+    ("Serial", proto::console::serial::Serial::<'static>::GUID),
+    ("ShimLock", proto::shim::ShimLock::GUID),
+    ("SimpleFileSystem", proto::media::fs::SimpleFileSystem::GUID),
+];
+
+pub fn test() {
+    check_spec_guids();
+    check_ascii_round_trip();
+
+    info!("Checking that protocol GUIDs are distinct");
+
+    for (i, (name, guid)) in PROTOCOL_GUIDS.iter().enumerate() {
+        for (other_name, other_guid) in &PROTOCOL_GUIDS[i + 1..] {
+            assert_ne!(
+                guid, other_guid,
+                "Protocols {} and {} have the same GUID",
+                name, other_name
+            );
+        }
+    }
+}
+
+/// Compare GUIDs which are easy to get wrong against the values given by
+/// the specification, in its `{ a, b, c, { d0, ..., d7 } }` form.
+fn check_spec_guids() {
+    info!("Checking protocol GUIDs against the specification");
+
+    // EFI_ACPI_TABLE_PROTOCOL_GUID
+    let acpi_table = Guid::from_values(
+        0xffe0_6bdd,
+        0x6107,
+        0x46a6,
+        0x7bb2,
+        [0x5a, 0x9c, 0x7e, 0xc5, 0x27, 0x5c],
+    );
+    assert_eq!(
+        proto::acpi_table::AcpiTable::GUID,
+        acpi_table,
+        "AcpiTable GUID does not match the specification"
+    );
+}
+
+/// Check that the textual form of every protocol GUID matches the `Display`
+/// output and parses back to the same GUID.
+fn check_ascii_round_trip() {
+    info!("Checking the textual form of protocol GUIDs");
+
+    for (name, guid) in PROTOCOL_GUIDS {
+        let text = guid.to_ascii_bytes();
+        assert_eq!(
+            &text[..],
+            format!("{}", guid).as_bytes(),
+            "Textual form of the {} GUID does not match its Display output",
+            name
+        );
+        assert_eq!(
+            Guid::from_ascii(&text),
+            Ok(*guid),
+            "{} GUID does not survive a round-trip through its textual form",
+            name
+        );
+        assert_eq!(
+            Guid::from_ascii(text.to_ascii_uppercase().as_slice()),
+            Ok(*guid),
+            "Uppercase textual form of the {} GUID does not parse",
+            name
+        );
+    }
+
+    assert!(Guid::from_ascii(b"").is_err());
+    assert!(Guid::from_ascii(b"ffe06bdd-6107-46a6-7bb2-5a9c7ec5275cc").is_err());
+    assert!(Guid::from_ascii(b"ffe06bdd-6107-46a6+7bb2-5a9c7ec5275c").is_err());
+    assert!(Guid::from_ascii(b"ffe06bdd-6107-46a6-7bb2-5a9c7ec5275g").is_err());
+}
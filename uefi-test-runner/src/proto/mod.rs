@@ -8,10 +8,14 @@ pub fn test(st: &SystemTable<Boot>) {
     let bt = st.boot_services();
 
     find_protocol(bt);
+    guid::test();
 
     console::test(st);
+    component_name::test();
     debug::test(bt);
+    driver_binding::test(bt);
     media::test(bt);
+    pci::test();
     pi::test(bt);
     shim::test(bt);
 }
@@ -29,8 +33,12 @@ fn find_protocol(bt: &BootServices) {
     );
 }
 
+mod component_name;
 mod console;
 mod debug;
+mod driver_binding;
+mod guid;
 mod media;
+mod pci;
 mod pi;
 mod shim;
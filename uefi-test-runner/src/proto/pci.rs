@@ -0,0 +1,138 @@
+use alloc::alloc::{alloc_zeroed, dealloc, Layout};
+use core::ffi::c_void;
+use uefi::proto::pci::direction::{BusMasterRead, BusMasterWrite};
+use uefi::proto::pci::{IoOperation, Mappable, OptionRom, PciIO};
+use uefi::table::boot::MemoryType;
+use uefi::{ResultExt, Status};
+
+pub fn test() {
+    info!("Running PCI option ROM parsing test");
+
+    check_pcir_within_image();
+    check_pcir_beyond_image();
+
+    // Miri cannot run this crate, which only runs as a UEFI application,
+    // so the pointers handed out by `MappingEx` are checked here instead
+    info!("Running PCI DMA mapping round-trip test");
+
+    check_boxed_round_trip();
+    check_common_buffer_round_trip();
+}
+
+/// Build a one block ROM image whose PCI data structure is at `pcir`.
+fn rom_image(rom: &mut [u8], pcir: usize) {
+    rom[0x00..0x02].copy_from_slice(&0xaa55u16.to_le_bytes());
+    rom[0x18..0x1a].copy_from_slice(&(pcir as u16).to_le_bytes());
+    rom[pcir..pcir + 4].copy_from_slice(b"PCIR");
+    rom[pcir + 0x04..pcir + 0x06].copy_from_slice(&0x8086u16.to_le_bytes());
+    // Image length of one 512 byte block, marked as the last image
+    rom[pcir + 0x10..pcir + 0x12].copy_from_slice(&1u16.to_le_bytes());
+    rom[pcir + 0x15] = 0x80;
+}
+
+fn check_pcir_within_image() {
+    let mut rom = [0u8; 512];
+    rom_image(&mut rom, 0x1c);
+
+    let mut images = OptionRom::new(&rom).images();
+    let image = images.next().expect("Valid ROM image was not parsed");
+    assert_eq!(image.vendor_id(), 0x8086);
+    assert_eq!(image.size(), 512);
+    assert!(image.is_last());
+    assert!(images.next().is_none());
+}
+
+/// A PCI data structure pointer past the declared image length must reject
+/// the image rather than panic when reading its fields.
+fn check_pcir_beyond_image() {
+    let mut rom = [0u8; 1024];
+    rom_image(&mut rom, 0x200);
+
+    assert!(OptionRom::new(&rom).images().next().is_none());
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct Descriptor {
+    addr: u64,
+    len: u32,
+    flags: u32,
+}
+
+impl Mappable for Descriptor {}
+
+const PAGE_SIZE: usize = 4096;
+
+/// Root bridge without an IOMMU, whose device addresses are host addresses.
+fn identity_pci() -> PciIO {
+    PciIO::mock()
+        .with_map(identity_map, identity_unmap)
+        .with_buffer(allocate_pages, free_pages)
+}
+
+extern "efiapi" fn identity_map(_: &PciIO, _: IoOperation, host_addr: *const c_void, _: &mut usize, device_addr: &mut u64, mapping: &mut *const c_void) -> Status {
+    *device_addr = host_addr as u64;
+    *mapping = host_addr;
+    Status::SUCCESS
+}
+
+extern "efiapi" fn identity_unmap(_: &PciIO, _: *const c_void) -> Status {
+    Status::SUCCESS
+}
+
+extern "efiapi" fn allocate_pages(_: &PciIO, _: u32, _: MemoryType, pages: usize, host_addr: &mut *mut c_void, _: u64) -> Status {
+    let layout = Layout::from_size_align(pages * PAGE_SIZE, PAGE_SIZE).unwrap();
+    *host_addr = unsafe { alloc_zeroed(layout) }.cast();
+    if host_addr.is_null() {
+        Status::OUT_OF_RESOURCES
+    } else {
+        Status::SUCCESS
+    }
+}
+
+extern "efiapi" fn free_pages(_: &PciIO, pages: usize, host_addr: *mut c_void) -> Status {
+    let layout = Layout::from_size_align(pages * PAGE_SIZE, PAGE_SIZE).unwrap();
+    unsafe { dealloc(host_addr.cast(), layout) };
+    Status::SUCCESS
+}
+
+/// Access the object the way the device would, through its device address.
+fn device_view(device_addr: u64) -> *mut Descriptor {
+    device_addr as usize as *mut Descriptor
+}
+
+const WRITTEN: Descriptor = Descriptor {
+    addr: 0x1000,
+    len: 512,
+    flags: 1,
+};
+
+fn check_boxed_round_trip() {
+    let pci = identity_pci();
+
+    let mut input = pci.map_ex::<Descriptor, BusMasterRead>().expect_success("Failed to map input");
+    unsafe { input.get_mut().write(WRITTEN) };
+    assert_eq!(unsafe { device_view(input.device_address()).read() }, WRITTEN);
+    drop(input);
+
+    let output = pci.map_ex::<Descriptor, BusMasterWrite>().expect_success("Failed to map output");
+    assert_eq!(unsafe { output.get().read() }, Descriptor::default());
+    unsafe { device_view(output.device_address()).write(WRITTEN) };
+    assert_eq!(unsafe { output.get().read() }, WRITTEN);
+}
+
+fn check_common_buffer_round_trip() {
+    let pci = identity_pci();
+
+    let mut ring = pci.map_array::<Descriptor, 4>().expect_success("Failed to map common buffer");
+    assert!(ring.as_slice().iter().all(|entry| *entry == Descriptor::default()));
+    ring.as_mut_slice()[1] = WRITTEN;
+    let device = device_view(ring.device_address());
+    assert_eq!(unsafe { device.add(1).read() }, WRITTEN);
+    unsafe { device.add(2).write(WRITTEN) };
+    assert_eq!(ring.as_slice()[2], WRITTEN);
+    for entry in ring.as_mut_slice() {
+        entry.flags = 0;
+    }
+    assert_eq!(unsafe { device.add(2).read() }.flags, 0);
+}